@@ -45,7 +45,7 @@ impl BlockRule for FerrisBlockScanner {
     // In "silent mode" (when `silent=true`) you aren't allowed to
     // create any nodes, should only increment `state.line`.
     //
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         // get contents of a line number `state.line` and check it
         let line = state.get_line(state.line).trim();
         if !line.starts_with(CRAB_CLAW) { return None; }
@@ -68,5 +68,5 @@ impl BlockRule for FerrisBlockScanner {
 
 pub fn add(md: &mut MarkdownIt) {
     // insert this rule into block subparser
-    md.block.add_rule::<FerrisBlockScanner>();
+    md.block.add_rule(FerrisBlockScanner);
 }
@@ -42,7 +42,7 @@ impl CoreRule for FerrisCounterRule {
     // It has `root` node of the AST as an argument and may modify its
     // contents as you like.
     //
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(&self, root: &mut Node, _: &MarkdownIt) {
         let mut counter = 0;
 
         // walk through AST recursively and count the number of two
@@ -60,5 +60,5 @@ impl CoreRule for FerrisCounterRule {
 
 pub fn add(md: &mut MarkdownIt) {
     // insert this rule into parser
-    md.add_rule::<FerrisCounterRule>();
+    md.add_rule(FerrisCounterRule);
 }
@@ -1,6 +1,6 @@
 // Replaces `(\/)` with `🦀`.
 
-use markdown_it::parser::inline::{InlineRule, InlineState};
+use markdown_it::parser::inline::{InlineRule, InlineState, Trigger};
 use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
 
 const CRAB_CLAW : &str = r#"(\/)"#;
@@ -31,7 +31,7 @@ struct FerrisInlineScanner;
 impl InlineRule for FerrisInlineScanner {
     // This is a character that starts your custom structure
     // (other characters may get skipped over).
-    const MARKER: char = '(';
+    fn trigger(&self) -> Trigger { Trigger::Char('(') }
 
     // This is a custom function that will be invoked on every character
     // in an inline context.
@@ -43,7 +43,7 @@ impl InlineRule for FerrisInlineScanner {
     //  - creates a new `Node` in AST
     //  - returns length of it
     //
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let input = &state.src[state.pos..state.pos_max]; // look for stuff at state.pos
         if !input.starts_with(CRAB_CLAW) { return None; } // return None if it's not found
 
@@ -57,5 +57,5 @@ impl InlineRule for FerrisInlineScanner {
 
 pub fn add(md: &mut MarkdownIt) {
     // insert this rule into inline subparser
-    md.inline.add_rule::<FerrisInlineScanner>();
+    md.inline.add_rule(FerrisInlineScanner);
 }
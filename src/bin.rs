@@ -1,8 +1,21 @@
+use markdown_it::parser::core::Root;
+use markdown_it::parser::diagnostics::Diagnostics;
 use markdown_it::parser::inline::{Text, TextSpecial};
 use std::io::{Read, Write};
 
 #[cfg(not(tarpaulin_include))]
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("lint") {
+        std::process::exit(lint());
+    }
+    if std::env::args().nth(1).as_deref() == Some("diff") {
+        std::process::exit(diff());
+    }
+    #[cfg(feature = "syntect")]
+    if std::env::args().nth(1).as_deref() == Some("css") {
+        std::process::exit(css());
+    }
+
     let mut input = "-".to_owned();
     let mut output = "-".to_owned();
     let mut no_html = false;
@@ -10,10 +23,15 @@ fn main() {
     let mut linkify = false;
     let mut typographer = false;
     let mut sourcepos = false;
+    let mut data_line = false;
+    let mut source_map = String::new();
     let mut show_tree = false;
+    #[cfg(feature = "syntect")]
+    let mut css_classes = false;
 
     {
         let mut cli = argparse::ArgumentParser::new();
+        cli.set_description("Convert markdown to html (use `lint` subcommand to check markdown style, `diff` to render changes between two files, `css` to generate stylesheet for highlighted code)");
 
         cli.add_option(&["-v", "--version"], argparse::Print(env!("CARGO_PKG_VERSION").to_owned()), "Show version");
 
@@ -25,6 +43,14 @@ fn main() {
             .refer(&mut sourcepos)
             .add_option(&["--sourcepos"], argparse::StoreTrue, "Include source mappings in HTML attributes");
 
+        cli
+            .refer(&mut data_line)
+            .add_option(&["--data-line"], argparse::StoreTrue, "Include start line of blocks in `data-line` attributes (for scroll sync)");
+
+        cli
+            .refer(&mut source_map)
+            .add_option(&["--source-map"], argparse::Store, "Write source map (v3) of generated html to a file");
+
         cli
             .refer(&mut no_html)
             .add_option(&["--no-html"], argparse::StoreTrue, "Disable embedded HTML");
@@ -38,6 +64,11 @@ fn main() {
             .refer(&mut typographer)
             .add_option(&["-t", "--typographer"], argparse::StoreTrue, "Enable smartquotes and other typographic replacements");
 
+        #[cfg(feature = "syntect")]
+        cli
+            .refer(&mut css_classes)
+            .add_option(&["--css-classes"], argparse::StoreTrue, "Highlight code with css classes instead of inline styles (see `css` subcommand)");
+
         cli
             .refer(&mut show_tree)
             .add_option(&["--tree"], argparse::StoreTrue, "Print syntax tree for debugging");
@@ -49,6 +80,8 @@ fn main() {
         cli.parse_args_or_exit();
     }
 
+    let filename = if input == "-" { "<stdin>".to_owned() } else { input.clone() };
+
     let vec = if input == "-" {
         let mut vec = Vec::new();
         std::io::stdin().read_to_end(&mut vec).unwrap();
@@ -61,7 +94,13 @@ fn main() {
     let md = &mut markdown_it::MarkdownIt::new();
     markdown_it::plugins::cmark::add(md);
     #[cfg(feature = "syntect")]
-    markdown_it::plugins::extra::syntect::add(md);
+    {
+        use markdown_it::plugins::extra::syntect;
+        syntect::add(md);
+        if css_classes {
+            syntect::set_output(md, syntect::HighlightOutput::Classes(syntect::ClassStyle::Spaced));
+        }
+    }
     markdown_it::plugins::extra::tables::add(md);
     markdown_it::plugins::extra::strikethrough::add(md);
     markdown_it::plugins::extra::beautify_links::add(md);
@@ -71,6 +110,9 @@ fn main() {
     if sourcepos {
         markdown_it::plugins::sourcepos::add(md);
     }
+    if data_line {
+        markdown_it::plugins::sourcepos::add_data_line(md);
+    }
     #[cfg(feature = "linkify")]
     if linkify {
         markdown_it::plugins::extra::linkify::add(md);
@@ -80,7 +122,12 @@ fn main() {
         markdown_it::plugins::extra::typographer::add(md);
     }
 
-    let ast = md.parse(&source);
+    let mut ast = md.parse(&source);
+
+    if let Some(diagnostics) = ast.cast_mut::<Root>().unwrap().ext.get_mut::<Diagnostics>() {
+        diagnostics.sort();
+        eprint!("{}", diagnostics.format(&source, &filename));
+    }
 
     if show_tree {
         ast.walk(|node, depth| {
@@ -97,7 +144,16 @@ fn main() {
         return;
     }
 
-    let result = ast.render();
+    let result = if source_map.is_empty() {
+        ast.render()
+    } else {
+        let (result, mut map) = ast.render_with_source_map(&source, &filename);
+        if output != "-" {
+            map.file = Some(output.clone());
+        }
+        std::fs::write(&source_map, map.to_json()).unwrap();
+        result
+    };
 
     if output == "-" {
         std::io::stdout().write_all(result.as_bytes()).unwrap();
@@ -105,3 +161,240 @@ fn main() {
         std::fs::write(output, &result).unwrap();
     }
 }
+
+#[cfg(not(tarpaulin_include))]
+fn lint() -> i32 {
+    let mut files : Vec<String> = Vec::new();
+    let mut disable : Vec<String> = Vec::new();
+    let mut max_line_length : usize = 80;
+    let mut errors = false;
+
+    {
+        let mut cli = argparse::ArgumentParser::new();
+        cli.set_description("Check markdown files for style issues");
+
+        cli
+            .refer(&mut disable)
+            .add_option(&["-d", "--disable"], argparse::Collect, "Disable rule (can be repeated)");
+
+        cli
+            .refer(&mut max_line_length)
+            .add_option(&["--max-line-length"], argparse::Store, "Maximum line length (default 80)");
+
+        cli
+            .refer(&mut errors)
+            .add_option(&["--errors"], argparse::StoreTrue, "Report issues as errors instead of warnings");
+
+        cli
+            .refer(&mut files)
+            .add_argument("files", argparse::List, "Files to check (stdin if none)");
+
+        let mut args: Vec<String> = std::env::args().collect();
+        let subcommand = args.remove(1);
+        args[0] = format!("{} {}", args[0], subcommand);
+        if let Err(code) = cli.parse(args, &mut std::io::stdout(), &mut std::io::stderr()) {
+            return code;
+        }
+    }
+
+    if files.is_empty() {
+        files.push("-".to_owned());
+    }
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::html::add(md);
+    markdown_it::plugins::extra::tables::add(md);
+    markdown_it::plugins::extra::strikethrough::add(md);
+
+    let mut linter = markdown_it::lint::Linter::new();
+    linter.get_mut::<markdown_it::lint::rules::LineLength>().unwrap().max = max_line_length;
+    for rule in disable.iter() {
+        if !linter.has_rule(rule) {
+            eprintln!("unknown rule: {}", rule);
+            return 2;
+        }
+        linter.remove(rule);
+    }
+    if errors {
+        let codes: Vec<_> = linter.codes().collect();
+        for code in codes {
+            linter.set_severity(code, markdown_it::parser::diagnostics::Severity::Error);
+        }
+    }
+
+    let mut found = 0;
+
+    for file in files.iter() {
+        let (filename, vec) = if file == "-" {
+            let mut vec = Vec::new();
+            std::io::stdin().read_to_end(&mut vec).unwrap();
+            ("<stdin>", vec)
+        } else {
+            match std::fs::read(file) {
+                Ok(vec) => (file.as_str(), vec),
+                Err(err) => {
+                    eprintln!("{}: {}", file, err);
+                    return 2;
+                }
+            }
+        };
+
+        let source = String::from_utf8_lossy(&vec);
+        let diagnostics = linter.lint(&md.parse(&source), &source);
+        found += diagnostics.len();
+        eprint!("{}", diagnostics.format(&source, filename));
+    }
+
+    if found > 0 { 1 } else { 0 }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn diff() -> i32 {
+    let mut old = String::new();
+    let mut new = String::new();
+    let mut output = "-".to_owned();
+    let mut no_html = false;
+
+    {
+        let mut cli = argparse::ArgumentParser::new();
+        cli.set_description("Render new version of a file as html, marking changes since old version");
+
+        cli
+            .refer(&mut output)
+            .add_option(&["-o", "--output"], argparse::Store, "File to write");
+
+        cli
+            .refer(&mut no_html)
+            .add_option(&["--no-html"], argparse::StoreTrue, "Disable embedded HTML");
+
+        cli
+            .refer(&mut old)
+            .add_argument("old", argparse::Store, "Old version of the file")
+            .required();
+
+        cli
+            .refer(&mut new)
+            .add_argument("new", argparse::Store, "New version of the file")
+            .required();
+
+        let mut args: Vec<String> = std::env::args().collect();
+        let subcommand = args.remove(1);
+        args[0] = format!("{} {}", args[0], subcommand);
+        if let Err(code) = cli.parse(args, &mut std::io::stdout(), &mut std::io::stderr()) {
+            return code;
+        }
+    }
+
+    let read = |file: &str| match std::fs::read(file) {
+        Ok(vec) => Some(String::from_utf8_lossy(&vec).into_owned()),
+        Err(err) => {
+            eprintln!("{}: {}", file, err);
+            None
+        }
+    };
+
+    let Some(old) = read(&old) else { return 2 };
+    let Some(new) = read(&new) else { return 2 };
+
+    let md = &mut markdown_it::MarkdownIt::new();
+    markdown_it::plugins::cmark::add(md);
+    markdown_it::plugins::extra::tables::add(md);
+    markdown_it::plugins::extra::strikethrough::add(md);
+    if !no_html {
+        markdown_it::plugins::html::add(md);
+    }
+
+    let result = markdown_it::parser::diff::render_diff(md, &old, &new);
+
+    if output == "-" {
+        std::io::stdout().write_all(result.as_bytes()).unwrap();
+    } else if let Err(err) = std::fs::write(&output, &result) {
+        eprintln!("{}: {}", output, err);
+        return 2;
+    }
+
+    0
+}
+
+#[cfg(feature = "syntect")]
+#[cfg(not(tarpaulin_include))]
+fn css() -> i32 {
+    use markdown_it::plugins::extra::syntect::{self, ClassStyle};
+
+    let mut theme = "InspiredGitHub".to_owned();
+    let mut dark = String::new();
+    let mut prefix = String::new();
+    let mut output = "-".to_owned();
+    let mut list = false;
+
+    {
+        let mut cli = argparse::ArgumentParser::new();
+        cli.set_description("Generate stylesheet for code highlighted with `--css-classes`");
+
+        cli
+            .refer(&mut output)
+            .add_option(&["-o", "--output"], argparse::Store, "File to write");
+
+        cli
+            .refer(&mut dark)
+            .add_option(&["--dark"], argparse::Store, "Theme used if user prefers dark color scheme");
+
+        cli
+            .refer(&mut prefix)
+            .add_option(&["--prefix"], argparse::Store, "Prefix for all css classes");
+
+        cli
+            .refer(&mut list)
+            .add_option(&["--list"], argparse::StoreTrue, "List available themes");
+
+        cli
+            .refer(&mut theme)
+            .add_argument("theme", argparse::Store, "Theme name (default InspiredGitHub)");
+
+        let mut args: Vec<String> = std::env::args().collect();
+        let subcommand = args.remove(1);
+        args[0] = format!("{} {}", args[0], subcommand);
+        if let Err(code) = cli.parse(args, &mut std::io::stdout(), &mut std::io::stderr()) {
+            return code;
+        }
+    }
+
+    if list {
+        let mut themes: Vec<_> = syntect::builtin_themes().collect();
+        themes.sort_unstable();
+        themes.iter().for_each(|name| println!("{}", name));
+        return 0;
+    }
+
+    let style = if prefix.is_empty() {
+        ClassStyle::Spaced
+    } else {
+        ClassStyle::SpacedPrefixed { prefix: Box::leak(prefix.into_boxed_str()) }
+    };
+
+    let get_theme = |name: &str| {
+        let theme = syntect::builtin_theme(name);
+        if theme.is_none() {
+            eprintln!("unknown theme: {} (use --list to see available themes)", name);
+        }
+        theme
+    };
+
+    let Some(light) = get_theme(&theme) else { return 2 };
+    let result = if dark.is_empty() {
+        syntect::css_for_theme(light, style)
+    } else {
+        let Some(dark) = get_theme(&dark) else { return 2 };
+        syntect::css_for_light_dark(light, dark, style)
+    };
+
+    if output == "-" {
+        std::io::stdout().write_all(result.as_bytes()).unwrap();
+    } else if let Err(err) = std::fs::write(&output, &result) {
+        eprintln!("{}: {}", output, err);
+        return 2;
+    }
+
+    0
+}
@@ -42,9 +42,9 @@ use std::slice::Iter;
 /// This data structure contains any number of elements (M, T), where T is any type and
 /// M (mark) is its identifier.
 ///
-///  - `M` is used for ordering and dependency checking, it must implement `Eq + Copy + Hash + Debug`
-/// . Common choices for `M` are `u32`, `&'static str`, or a special `Symbol` type
-/// designed for this purpose.
+///  - `M` is used for ordering and dependency checking, it must implement `Eq + Clone + Hash + Debug`.
+///    Common choices for `M` are `u32`, `&'static str`, or a special `Symbol` type
+///    designed for this purpose.
 ///
 ///  - `T` is any user-defined type. It's usually a function or boxed trait.
 ///
@@ -59,7 +59,7 @@ impl<M, T> Ruler<M, T> {
     }
 }
 
-impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
+impl<M: Eq + Hash + Clone + Debug, T: Clone> Ruler<M, T> {
     /// Add a new rule identified by `mark` with payload `value`.
     pub fn add(&mut self, mark: M, value: T) -> &mut RuleItem<M, T> {
         self.compiled = OnceCell::new();
@@ -70,6 +70,7 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
 
     /// Remove all rules identified by `mark`.
     pub fn remove(&mut self, mark: M) {
+        self.compiled = OnceCell::new();
         self.deps.retain(|dep| !dep.marks.contains(&mark));
     }
 
@@ -78,9 +79,15 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
         self.deps.iter().any(|dep| dep.marks.contains(&mark))
     }
 
+    /// Get mutable access to the payload of the first rule identified by `mark`.
+    pub fn get_mut(&mut self, mark: M) -> Option<&mut T> {
+        self.compiled = OnceCell::new();
+        self.deps.iter_mut().find(|dep| dep.marks.contains(&mark)).map(|dep| &mut dep.value)
+    }
+
     /// Ordered iteration through rules.
     #[inline]
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.compiled.get_or_init(|| self.compile()).1.iter()
     }
 
@@ -119,7 +126,7 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
                 }
             }
             for mark in &dep.marks {
-                idhash.entry(*mark).or_default().push(idx);
+                idhash.entry(mark.clone()).or_default().push(idx);
             }
         }
 
@@ -130,12 +137,12 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
             for constraint in &dep.cons {
                 match constraint {
                     RuleItemConstraint::Before(v) => {
-                        for depidx in idhash.entry(*v).or_default().iter() {
+                        for depidx in idhash.entry(v.clone()).or_default().iter() {
                             deps_graph.get_mut(*depidx).unwrap().insert(idx);
                         }
                     }
                     RuleItemConstraint::After(v) => {
-                        for depidx in idhash.entry(*v).or_default().iter() {
+                        for depidx in idhash.entry(v.clone()).or_default().iter() {
                             deps_graph.get_mut(idx).unwrap().insert(*depidx);
                         }
                     }
@@ -210,11 +217,11 @@ impl<M: Eq + Hash + Copy + Debug, T: Clone> Ruler<M, T> {
     }
 }
 
-impl<M: Eq + Hash + Copy + Debug, T: Clone> Debug for Ruler<M, T> {
+impl<M: Eq + Hash + Clone + Debug, T: Clone> Debug for Ruler<M, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let vec: Vec<(usize, M)> = self.compiled.get_or_init(|| self.compile()).0
                                     .iter()
-                                    .map(|idx| (*idx, *self.deps.get(*idx).unwrap().marks.first().unwrap()))
+                                    .map(|idx| (*idx, self.deps.get(*idx).unwrap().marks.first().unwrap().clone()))
                                     .collect();
 
         f.debug_struct("Ruler")
@@ -257,7 +264,7 @@ impl<M, T> RuleItem<M, T> {
     }
 }
 
-impl<M: Copy, T> RuleItem<M, T> {
+impl<M, T> RuleItem<M, T> {
     /// Make sure this rule will be inserted before any rule defined by `mark` (if such rule exists).
     /// ```
     /// use markdown_it::common::ruler::Ruler;
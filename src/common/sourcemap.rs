@@ -1,70 +1,178 @@
 //! Tools to work with source positions and mapping.
 
-#[derive(Debug)]
-/// Holds source code, allows to calculate `line:column` from byte offset.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+/// Units used to count columns.
+pub enum ColumnEncoding {
+    /// UTF-8 bytes.
+    Bytes,
+    /// Unicode scalar values.
+    #[default]
+    Chars,
+    /// UTF-16 code units (used by LSP and JavaScript).
+    Utf16,
+}
+
+#[derive(Debug, Clone)]
+/// Line starts of source code, allows to calculate `line:column` from byte offset
+/// and back, both in `O(log n)`.
+///
+/// Lines and columns start from 1, columns are counted in units of [ColumnEncoding].
+///
+/// ```rust
+/// use markdown_it::common::sourcemap::{ColumnEncoding, SourceWithLineStarts};
+///
+/// let source = "# ☃\n🦀 *crab*";
+/// let map = SourceWithLineStarts::with_encoding(source, ColumnEncoding::Utf16);
+/// assert_eq!(map.get_line_column(source.find('*').unwrap()), (2, 4));
+/// assert_eq!(map.get_byte_offset(2, 4), source.find('*'));
+/// ```
 pub struct SourceWithLineStarts {
-    src: String,
-    marks: Vec<CharMappingMark>,
+    len: usize,
+    encoding: ColumnEncoding,
+    line_starts: Vec<usize>,
+    line_ends: Vec<usize>,
+    wide_chars: Vec<WideChar>,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Non-ASCII character along with the amount of chars and UTF-16 units before it.
+struct WideChar {
+    offset: usize,
+    bytes: usize,
+    chars_before: usize,
+    utf16_before: usize,
 }
 
 impl SourceWithLineStarts {
     pub fn new(src: &str) -> Self {
+        Self::with_encoding(src, ColumnEncoding::default())
+    }
+
+    pub fn with_encoding(src: &str, encoding: ColumnEncoding) -> Self {
+        let mut line_starts = vec![0];
+        let mut line_ends = Vec::new();
+        let mut crlf = None;
+        let mut wide_chars = Vec::new();
+        let mut chars_before = 0;
+        let mut utf16_before = 0;
         let mut iterator = src.char_indices().peekable();
-        let mut line = 1;
-        let mut column = 0;
-        let mut marks = vec![CharMappingMark { offset: 0, line, column }];
-
-        loop {
-            match iterator.next() {
-                Some((_, '\r')) if matches!(iterator.peek(), Some((_, '\n'))) => {
-                    // ignore \r followed by \n
-                    column += 1;
+
+        while let Some((offset, ch)) = iterator.next() {
+            match ch {
+                // \r followed by \n is a single linebreak
+                '\r' if matches!(iterator.peek(), Some((_, '\n'))) => crlf = Some(offset),
+                '\r' | '\n' => {
+                    line_ends.push(crlf.take().unwrap_or(offset));
+                    line_starts.push(offset + 1);
                 }
-                Some((offset, '\r' | '\n')) => {
-                    // \r or \n are linebreaks
-                    line += 1;
-                    column = 0;
-                    marks.push(CharMappingMark { offset: offset + 1, line, column });
+                _ if !ch.is_ascii() => {
+                    wide_chars.push(WideChar { offset, bytes: ch.len_utf8(), chars_before, utf16_before });
                 }
-                Some((offset, _)) => {
-                    // any other character, just increase position
-                    if column % 16 == 0 && column > 0 {
-                        marks.push(CharMappingMark { offset, line, column });
-                    }
-                    column += 1;
-                },
-                None => break,
+                _ => {}
             }
+            chars_before += 1;
+            utf16_before += ch.len_utf16();
         }
 
-        Self { src: src.to_owned(), marks }
+        line_ends.push(src.len());
+
+        Self { len: src.len(), encoding, line_starts, line_ends, wide_chars }
     }
 
-    fn get_position(&self, byte_offset: usize) -> (u32, u32) {
-        let byte_offset = byte_offset + 1; // include current char
-        let found = match self.marks.binary_search_by(|mark| mark.offset.cmp(&byte_offset)) {
-            Ok(x) => x,
-            Err(x) => x - 1,
+    pub fn encoding(&self) -> ColumnEncoding {
+        self.encoding
+    }
+
+    /// Number of lines in the source.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte offset of the start of a line.
+    pub fn line_start(&self, line: u32) -> Option<usize> {
+        self.line_starts.get((line as usize).checked_sub(1)?).copied()
+    }
+
+    /// Line and column of a character at given byte offset
+    /// (offsets past the end of the source are clamped to it).
+    pub fn get_line_column(&self, byte_offset: usize) -> (u32, u32) {
+        let byte_offset = byte_offset.min(self.len);
+        let line = self.find_line(byte_offset);
+        let column = self.units_before(byte_offset) - self.units_before(self.line_starts[line]);
+        (line as u32 + 1, column as u32 + 1)
+    }
+
+    /// Byte offset of a character at given line and column, columns past the end
+    /// of the line are clamped to it; returns `None` if line doesn't exist.
+    pub fn get_byte_offset(&self, line: u32, column: u32) -> Option<usize> {
+        let line_idx = (line as usize).checked_sub(1)?;
+        let line_start = *self.line_starts.get(line_idx)?;
+        let line_end = self.line_ends[line_idx];
+
+        let target = self.units_before(line_start) + (column.max(1) as usize - 1);
+        let offset = match self.wide_chars.partition_point(|ch| self.char_units_before(ch) < target) {
+            0 => target,
+            idx => {
+                let ch = &self.wide_chars[idx - 1];
+                let after = self.char_units_before(ch) + self.char_units(ch);
+                if target < after {
+                    // column points inside of a character (e.g. second half of a surrogate pair)
+                    ch.offset
+                } else {
+                    ch.offset + ch.bytes + (target - after)
+                }
+            }
         };
-        let mark = &self.marks[found];
-        let line = mark.line;
-        let mut column = mark.column;
-        for (offset, _) in self.src[mark.offset..].char_indices() {
-            if mark.offset + offset >= byte_offset { break; }
-            column += 1;
+
+        Some(offset.clamp(line_start, line_end))
+    }
+
+    fn find_line(&self, byte_offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= byte_offset) - 1
+    }
+
+    /// Amount of columns before given byte offset (counting from the start of the source),
+    /// a character is counted if offset points inside of it.
+    fn units_before(&self, byte_offset: usize) -> usize {
+        if self.encoding == ColumnEncoding::Bytes { return byte_offset; }
+
+        match self.wide_chars.partition_point(|ch| ch.offset < byte_offset) {
+            0 => byte_offset,
+            idx => {
+                let ch = &self.wide_chars[idx - 1];
+                let after = self.char_units_before(ch) + self.char_units(ch);
+                after + byte_offset.saturating_sub(ch.offset + ch.bytes)
+            }
         }
-        (line, column)
     }
-}
 
-#[derive(Debug)]
-struct CharMappingMark {
-    offset: usize,
-    line: u32,
-    column: u32,
+    fn char_units_before(&self, ch: &WideChar) -> usize {
+        match self.encoding {
+            ColumnEncoding::Bytes => ch.offset,
+            ColumnEncoding::Chars => ch.chars_before,
+            ColumnEncoding::Utf16 => ch.utf16_before,
+        }
+    }
+
+    fn char_units(&self, ch: &WideChar) -> usize {
+        match self.encoding {
+            ColumnEncoding::Bytes => ch.bytes,
+            ColumnEncoding::Chars => 1,
+            ColumnEncoding::Utf16 => if ch.bytes == 4 { 2 } else { 1 },
+        }
+    }
+
+    /// Position in the format used by `data-sourcepos`: column of the last byte
+    /// of the character at given offset, line breaks belong to the next line (column 0).
+    fn get_position(&self, byte_offset: usize) -> (u32, u32) {
+        let end = (byte_offset + 1).min(self.len);
+        let line = self.find_line(end);
+        let column = self.units_before(end) - self.units_before(self.line_starts[line]);
+        (line as u32 + 1, column as u32)
+    }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
 /// Positions of the start and the end of an AST node.
 pub struct SourcePos {
     byte_offset: (usize, usize),
@@ -99,8 +207,125 @@ impl std::fmt::Debug for SourcePos {
     }
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Source map in [v3 format](https://sourcemaps.info/spec.html), mapping positions
+/// in generated file to positions in source files.
+pub struct SourceMap {
+    /// Name of the generated file.
+    pub file: Option<String>,
+    /// Names of source files.
+    pub sources: Vec<String>,
+    /// Contents of source files (same length as `sources`, if present).
+    pub sources_content: Vec<String>,
+    /// Base64 VLQ encoded mappings.
+    pub mappings: String,
+}
+
+impl SourceMap {
+    /// Create a source map for `generated` text from a single `source` file, using pairs of
+    /// byte offsets `(generated, source)` sorted by generated offset. Each pair marks
+    /// a position from which generated text corresponds to given position in the source.
+    ///
+    /// Columns are counted in UTF-16 code units, as expected by browsers.
+    pub fn from_offsets(generated: &str, source: &str, source_name: &str, offsets: &[(usize, usize)]) -> Self {
+        let generated_map = SourceWithLineStarts::with_encoding(generated, ColumnEncoding::Utf16);
+        let source_map = SourceWithLineStarts::with_encoding(source, ColumnEncoding::Utf16);
+
+        let mut mappings = String::new();
+        let mut line = 1;
+        let mut prev_column = 0;
+        let mut prev_source = (0, 0);
+        let mut first_in_line = true;
+
+        for (idx, &(generated_offset, source_offset)) in offsets.iter().enumerate() {
+            // only the last one of several mappings at the same position matters
+            if offsets.get(idx + 1).is_some_and(|next| next.0 == generated_offset) { continue; }
+            if generated_offset >= generated.len() { break; }
+
+            let (gen_line, gen_column) = generated_map.get_line_column(generated_offset);
+            let (src_line, src_column) = source_map.get_line_column(source_offset);
+            let (src_line, src_column) = (src_line as i64 - 1, src_column as i64 - 1);
+
+            while line < gen_line {
+                mappings.push(';');
+                line += 1;
+                prev_column = 0;
+                first_in_line = true;
+            }
+
+            if !first_in_line { mappings.push(','); }
+            first_in_line = false;
+
+            let gen_column = gen_column as i64 - 1;
+            encode_vlq(&mut mappings, gen_column - prev_column);
+            encode_vlq(&mut mappings, 0);
+            encode_vlq(&mut mappings, src_line - prev_source.0);
+            encode_vlq(&mut mappings, src_column - prev_source.1);
+            prev_column = gen_column;
+            prev_source = (src_line, src_column);
+        }
+
+        Self {
+            file: None,
+            sources: vec![source_name.to_owned()],
+            sources_content: vec![source.to_owned()],
+            mappings,
+        }
+    }
+
+    /// Serialize source map to JSON.
+    pub fn to_json(&self) -> String {
+        fn quote(str: &str) -> String {
+            let mut result = String::with_capacity(str.len() + 2);
+            result.push('"');
+            for ch in str.chars() {
+                match ch {
+                    '"' => result.push_str("\\\""),
+                    '\\' => result.push_str("\\\\"),
+                    '\n' => result.push_str("\\n"),
+                    '\r' => result.push_str("\\r"),
+                    '\t' => result.push_str("\\t"),
+                    ch if (ch as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", ch as u32)),
+                    ch => result.push(ch),
+                }
+            }
+            result.push('"');
+            result
+        }
+
+        fn list(items: &[String]) -> String {
+            items.iter().map(|item| quote(item)).collect::<Vec<_>>().join(",")
+        }
+
+        let mut json = String::from("{\"version\":3,");
+        if let Some(file) = &self.file {
+            json.push_str(&format!("\"file\":{},", quote(file)));
+        }
+        json.push_str(&format!("\"sources\":[{}],", list(&self.sources)));
+        if !self.sources_content.is_empty() {
+            json.push_str(&format!("\"sourcesContent\":[{}],", list(&self.sources_content)));
+        }
+        json.push_str(&format!("\"names\":[],\"mappings\":{}}}", quote(&self.mappings)));
+        json
+    }
+}
+
+fn encode_vlq(out: &mut String, value: i64) {
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut value = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+    loop {
+        let mut digit = value & 0b11111;
+        value >>= 5;
+        if value > 0 { digit |= 0b100000; }
+        out.push(BASE64[digit as usize] as char);
+        if value == 0 { break; }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::ColumnEncoding;
+    use super::SourceMap;
     use super::SourceWithLineStarts;
     use super::SourcePos;
 
@@ -140,4 +365,59 @@ mod tests {
         let map = SourceWithLineStarts::new("123\n456");
         assert_eq!(SourcePos::new(100, 0).get_positions(&map).0, (2, 3));
     }
+
+    #[test]
+    fn column_encodings() {
+        let source = "aé😀b\nc";
+        let offset = source.find('b').unwrap();
+        let position = |encoding| SourceWithLineStarts::with_encoding(source, encoding).get_line_column(offset);
+        assert_eq!(position(ColumnEncoding::Bytes), (1, 8));
+        assert_eq!(position(ColumnEncoding::Chars), (1, 4));
+        assert_eq!(position(ColumnEncoding::Utf16), (1, 5));
+        assert_eq!(SourceWithLineStarts::new(source).get_line_column(offset + 2), (2, 1));
+    }
+
+    #[test]
+    fn reverse_mapping() {
+        let source = "aé😀b\r\nc\rd";
+        for encoding in [ColumnEncoding::Bytes, ColumnEncoding::Chars, ColumnEncoding::Utf16] {
+            let map = SourceWithLineStarts::with_encoding(source, encoding);
+            for (offset, ch) in source.char_indices() {
+                // \n after \r is not addressable, columns past the end of line point to \r
+                if ch == '\n' && source[..offset].ends_with('\r') { continue; }
+                let (line, column) = map.get_line_column(offset);
+                assert_eq!(map.get_byte_offset(line, column), Some(offset), "{:?} {}", encoding, offset);
+            }
+        }
+
+        let map = SourceWithLineStarts::with_encoding(source, ColumnEncoding::Utf16);
+        assert_eq!(map.line_count(), 3);
+        assert_eq!(map.line_start(2), Some(source.find('c').unwrap()));
+        // second half of a surrogate pair
+        assert_eq!(map.get_byte_offset(1, 4), Some(3));
+        // past the end of line
+        assert_eq!(map.get_byte_offset(1, 100), Some(source.find('\r').unwrap()));
+        assert_eq!(map.get_byte_offset(3, 100), Some(source.len()));
+        assert_eq!(map.get_byte_offset(4, 1), None);
+        assert_eq!(map.get_byte_offset(0, 1), None);
+    }
+
+    #[test]
+    fn vlq() {
+        let mut out = String::new();
+        for value in [0, 1, -1, 15, 16, -16, 1000] {
+            super::encode_vlq(&mut out, value);
+            out.push(' ');
+        }
+        assert_eq!(out, "A C D e gB hB w+B ");
+    }
+
+    #[test]
+    fn source_map_json() {
+        let map = SourceMap::from_offsets("<p>x</p>\n<p>é\"y</p>", "x\n\né\"y", "a.md", &[
+            (0, 0), (3, 0), (9, 3), (12, 3), (12, 6),
+        ]);
+        assert_eq!(map.mappings, "AAAA,GAAA;AAEA,GAAE");
+        assert_eq!(map.to_json(), r#"{"version":3,"sources":["a.md"],"sourcesContent":["x\n\né\"y"],"names":[],"mappings":"AAAA,GAAA;AAEA,GAAE"}"#);
+    }
 }
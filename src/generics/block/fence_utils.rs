@@ -0,0 +1,55 @@
+use crate::parser::block::BlockState;
+
+/// Scan opening fence at current line: `marker` repeated at least `min_len` times,
+/// followed by an info string. Returns marker length and trimmed info string.
+pub(super) fn scan_header<'a>(state: &'a BlockState, marker: char, min_len: usize) -> Option<(usize, &'a str)> {
+    if state.line_indent(state.line) >= state.md.max_indent { return None; }
+
+    let line = state.get_line(state.line);
+    let len = line.chars().take_while(|&ch| ch == marker).count();
+
+    if len < min_len { return None; }
+
+    Some((len, line[len * marker.len_utf8()..].trim()))
+}
+
+/// Search for a closing fence starting from the line after current one.
+/// Returns the line where block content ends, and whether closing fence was found.
+pub(super) fn find_end(state: &BlockState, marker: char, marker_len: usize) -> (usize, bool) {
+    let mut next_line = state.line;
+
+    'outer: loop {
+        next_line += 1;
+        if next_line >= state.line_max {
+            // unclosed block should be autoclosed by end of document.
+            // also block seems to be autoclosed by end of parent
+            return (next_line, false);
+        }
+
+        let line = state.get_line(next_line);
+
+        if !line.is_empty() && state.line_indent(next_line) < 0 {
+            // non-empty line with negative indent should stop the list
+            return (next_line, false);
+        }
+
+        if state.line_indent(next_line) >= state.md.max_indent { continue; }
+
+        let mut chars = line.chars().peekable();
+        let mut len_end = 0;
+        while Some(&marker) == chars.peek() {
+            chars.next();
+            len_end += 1;
+        }
+
+        // closing fence must be at least as long as the opening one
+        if len_end < marker_len { continue; }
+
+        // make sure tail has spaces only
+        for ch in chars {
+            if ch != ' ' && ch != '\t' { continue 'outer; }
+        }
+
+        return (next_line, true);
+    }
+}
@@ -0,0 +1,109 @@
+//! Structure similar to ` ```code fence``` ` with configurable marker, content is parsed as markdown.
+//!
+//! It allows you to define a custom container that starts with a line of repeated
+//! marker characters followed by an info string, and ends with a line of at least
+//! the same number of markers (or with the end of the parent block). Everything in
+//! between is parsed as nested markdown and added as children of your node.
+//!
+//! There are many structures that can be implemented with this, for example:
+//!  - `::: warning` admonitions
+//!  - `:::: columns` layouts (nested containers use different marker lengths)
+//!  - `<<< details` spoilers
+//!
+//! You add a custom structure by using [add_with] function, which takes following arguments:
+//!  - `md` - parser instance
+//!  - `marker` - marker character
+//!  - `min_len` - minimum number of markers in the opening sequence
+//!  - `f` - function that receives info string and should return your custom [Node]
+//!    (or `None` if this block should be handled by other rules)
+//!
+//! Here is an example of implementing `::: warning` containers:
+//!
+//! ```rust
+//! use markdown_it::generics::block::fenced_container;
+//! use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+//!
+//! #[derive(Debug)]
+//! struct Warning;
+//! impl NodeValue for Warning {
+//!     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+//!         fmt.cr();
+//!         fmt.open("div", &[("class", "warning".into())]);
+//!         fmt.cr();
+//!         fmt.contents(&node.children);
+//!         fmt.cr();
+//!         fmt.close("div");
+//!         fmt.cr();
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! fenced_container::add_with(md, ':', 3, |info| {
+//!     (info == "warning").then(|| Node::new(Warning))
+//! });
+//!
+//! let html = md.parse("::: warning\n*here be dragons*\n:::").render();
+//! assert_eq!(html.trim(), r#"<div class="warning">
+//! <p><em>here be dragons</em></p>
+//! </div>"#);
+//! ```
+//!
+use crate::generics::block::fence_utils::{find_end, scan_header};
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::core::RuleId;
+use crate::{MarkdownIt, Node};
+
+pub fn add_with(
+    md: &mut MarkdownIt,
+    marker: char,
+    min_len: usize,
+    f: impl Fn (&str) -> Option<Node> + Send + Sync + 'static,
+) {
+    md.block.add_rule_with_id(rule_id(marker), FencedContainerScanner {
+        marker,
+        min_len,
+        f: Box::new(f),
+    });
+}
+
+/// Id of the block rule that handles structures with given `marker`.
+pub fn rule_id(marker: char) -> RuleId {
+    format!("fenced_container:{}", marker).into()
+}
+
+#[doc(hidden)]
+pub struct FencedContainerScanner {
+    marker: char,
+    min_len: usize,
+    f: Box<dyn Fn (&str) -> Option<Node> + Send + Sync>,
+}
+
+impl BlockRule for FencedContainerScanner {
+    fn check(&self, state: &mut BlockState) -> Option<()> {
+        // only the opening marker is checked, user callback is called when the block is parsed
+        scan_header(state, self.marker, self.min_len).map(|_| ())
+    }
+
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
+        let (len, info) = scan_header(state, self.marker, self.min_len)?;
+        let node = (self.f)(info)?;
+        let (next_line, have_end_marker) = find_end(state, self.marker, len);
+
+        let start_line = state.line;
+        let old_node = std::mem::replace(&mut state.node, node);
+        let old_line_max = state.line_max;
+
+        // this will prevent lazy continuations from ever going past our end marker
+        state.line = start_line + 1;
+        state.line_max = next_line;
+        state.level += 1;
+        state.md.block.tokenize(state);
+        state.level -= 1;
+        state.line = start_line;
+        state.line_max = old_line_max;
+
+        let node = std::mem::replace(&mut state.node, old_node);
+        Some((node, next_line - start_line + if have_end_marker { 1 } else { 0 }))
+    }
+}
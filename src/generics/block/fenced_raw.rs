@@ -0,0 +1,94 @@
+//! Structure similar to ` ```code fence``` ` with configurable marker, content is kept verbatim.
+//!
+//! It allows you to define a custom block that starts with a line of repeated
+//! marker characters followed by an info string, and ends with a line of at least
+//! the same number of markers (or with the end of the document). Everything in
+//! between is passed to your function as is, without any markdown processing.
+//!
+//! There are many structures that can be implemented with this, for example:
+//!  - `$$ display math $$` blocks
+//!  - `%%% diagrams %%%` passed to an external renderer
+//!  - front matter-like blocks separated with `+++`
+//!
+//! You add a custom structure by using [add_with] function, which takes following arguments:
+//!  - `md` - parser instance
+//!  - `marker` - marker character
+//!  - `min_len` - minimum number of markers in the opening sequence
+//!  - `f` - function that receives info string and block content, and should return
+//!    your custom [Node] (or `None` if this block should be handled by other rules)
+//!
+//! Here is an example of a rule turning `$$` blocks into display math:
+//!
+//! ```rust
+//! use markdown_it::generics::block::fenced_raw;
+//! use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+//!
+//! #[derive(Debug)]
+//! struct DisplayMath(String);
+//! impl NodeValue for DisplayMath {
+//!     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+//!         fmt.cr();
+//!         fmt.open("div", &[("class", "math".into())]);
+//!         fmt.text(&self.0);
+//!         fmt.close("div");
+//!         fmt.cr();
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! fenced_raw::add_with(md, '$', 2, |_info, content| Some(Node::new(DisplayMath(content))));
+//!
+//! let html = md.parse("$$\na < b\n$$").render();
+//! assert_eq!(html.trim(), r#"<div class="math">a &lt; b
+//! </div>"#);
+//! ```
+//!
+use crate::generics::block::fence_utils::{find_end, scan_header};
+use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::core::RuleId;
+use crate::{MarkdownIt, Node};
+
+pub fn add_with(
+    md: &mut MarkdownIt,
+    marker: char,
+    min_len: usize,
+    f: impl Fn (&str, String) -> Option<Node> + Send + Sync + 'static,
+) {
+    md.block.add_rule_with_id(rule_id(marker), FencedRawScanner {
+        marker,
+        min_len,
+        f: Box::new(f),
+    });
+}
+
+/// Id of the block rule that handles structures with given `marker`.
+pub fn rule_id(marker: char) -> RuleId {
+    format!("fenced_raw:{}", marker).into()
+}
+
+#[doc(hidden)]
+pub struct FencedRawScanner {
+    marker: char,
+    min_len: usize,
+    f: Box<dyn Fn (&str, String) -> Option<Node> + Send + Sync>,
+}
+
+impl BlockRule for FencedRawScanner {
+    fn check(&self, state: &mut BlockState) -> Option<()> {
+        // only the opening marker is checked, user callback is called when the block is parsed
+        scan_header(state, self.marker, self.min_len).map(|_| ())
+    }
+
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
+        let (len, info) = scan_header(state, self.marker, self.min_len)?;
+        let info = info.to_owned();
+        let (next_line, have_end_marker) = find_end(state, self.marker, len);
+
+        // If a fence has heading spaces, they should be removed from its inner block
+        let indent = state.line_offsets[state.line].indent_nonspace;
+        let (content, _) = state.get_lines(state.line + 1, next_line, indent as usize, true);
+
+        let node = (self.f)(&info, content)?;
+        Some((node, next_line - state.line + if have_end_marker { 1 } else { 0 }))
+    }
+}
@@ -0,0 +1,206 @@
+//! Structure similar to `> blockquote` with configurable marker at the start of every line.
+//!
+//! It allows you to define a custom block where every line is prefixed with
+//! a marker character. Marker (and one optional space after it) is removed,
+//! and the rest is parsed as nested markdown. Just like blockquotes, these blocks
+//! support lazy continuation lines without a marker.
+//!
+//! There are many structures that can be implemented with this, for example:
+//!  - `> blockquotes` in CommonMark
+//!  - `| line blocks` that preserve line breaks
+//!  - `! callouts` or `? spoilers`
+//!
+//! You add a custom structure by using [add_with] function, which takes following arguments:
+//!  - `md` - parser instance
+//!  - `marker` - marker character
+//!  - `f` - function that should return your custom [Node]
+//!
+//! Here is an example of implementing `!` callouts:
+//!
+//! ```rust
+//! use markdown_it::generics::block::line_prefix;
+//! use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+//!
+//! #[derive(Debug)]
+//! struct Callout;
+//! impl NodeValue for Callout {
+//!     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+//!         fmt.cr();
+//!         fmt.open("aside", &node.attrs);
+//!         fmt.cr();
+//!         fmt.contents(&node.children);
+//!         fmt.cr();
+//!         fmt.close("aside");
+//!         fmt.cr();
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! line_prefix::add_with(md, '!', || Node::new(Callout));
+//!
+//! let html = md.parse("! **note**\n! read this").render();
+//! assert_eq!(html.trim(), "<aside>\n<p><strong>note</strong>\nread this</p>\n</aside>");
+//! ```
+//!
+use crate::common::utils::find_indent_of;
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
+use crate::parser::core::RuleId;
+use crate::{MarkdownIt, Node};
+
+pub fn add_with(md: &mut MarkdownIt, marker: char, f: impl Fn () -> Node + Send + Sync + 'static) {
+    md.block.add_rule_with_id(rule_id(marker), LinePrefixScanner::new(marker, f));
+}
+
+/// Id of the block rule that handles structures with given `marker`.
+pub fn rule_id(marker: char) -> RuleId {
+    format!("line_prefix:{}", marker).into()
+}
+
+#[doc(hidden)]
+pub struct LinePrefixScanner {
+    marker: char,
+    f: Box<dyn Fn () -> Node + Send + Sync>,
+}
+
+impl LinePrefixScanner {
+    /// Create a scanner without adding it, so it can be wrapped in a rule with its own type.
+    pub fn new(marker: char, f: impl Fn () -> Node + Send + Sync + 'static) -> Self {
+        Self { marker, f: Box::new(f) }
+    }
+}
+
+impl BlockRule for LinePrefixScanner {
+    fn check(&self, state: &mut BlockState) -> Option<()> {
+
+        if state.line_indent(state.line) >= state.md.max_indent { return None; }
+
+        // check the marker
+        if !state.get_line(state.line).starts_with(self.marker) { return None; }
+
+        Some(())
+    }
+
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
+        self.check(state)?;
+
+        let mut old_line_offsets = Vec::new();
+        let start_line = state.line;
+        let mut next_line = state.line;
+        let mut last_line_empty = false;
+
+        // Search the end of the block
+        //
+        // Block ends with either (examples are for blockquotes):
+        //  1. an empty line outside:
+        //     ```
+        //     > test
+        //
+        //     ```
+        //  2. an empty line inside:
+        //     ```
+        //     >
+        //     test
+        //     ```
+        //  3. another tag:
+        //     ```
+        //     > test
+        //      - - -
+        //     ```
+        while next_line < state.line_max {
+            // check if it's outdented, i.e. it's inside list item and indented
+            // less than said list item:
+            //
+            // ```
+            // 1. anything
+            //    > current block
+            // 2. checking this line
+            // ```
+            let is_outdented = state.line_indent(next_line) < 0;
+            let line = state.get_line(next_line).to_owned();
+            let mut chars = line.chars();
+
+            match chars.next() {
+                None => {
+                    // Case 1: line is not inside the block, and this line is empty.
+                    break;
+                }
+                Some(ch) if ch == self.marker && !is_outdented => {
+                    // This line is inside the block.
+
+                    // set offset past spaces and marker
+                    let offsets = &state.line_offsets[next_line];
+                    let pos_after_marker = offsets.first_nonspace + ch.len_utf8();
+
+                    old_line_offsets.push(state.line_offsets[next_line].clone());
+
+                    let ( mut indent_after_marker, first_nonspace ) = find_indent_of(
+                        &state.src[offsets.line_start..offsets.line_end],
+                        pos_after_marker - offsets.line_start);
+
+                    last_line_empty = first_nonspace == offsets.line_end - offsets.line_start;
+
+                    // skip one optional space after marker
+                    if matches!(chars.next(), Some(' ' | '\t')) {
+                        indent_after_marker -= 1;
+                    }
+
+                    state.line_offsets[next_line].indent_nonspace = indent_after_marker as i32;
+                    state.line_offsets[next_line].first_nonspace = first_nonspace + state.line_offsets[next_line].line_start;
+                    next_line += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Case 2: line is not inside the block, and the last line was empty.
+            if last_line_empty { break; }
+
+            // Case 3: another tag found.
+            state.line = next_line;
+
+            if state.test_rules_at_line(InterruptTarget::Blockquote) {
+                if state.blk_indent != 0 {
+                    // state.blkIndent was non-zero, we now set it to zero,
+                    // so we need to re-calculate all offsets to appear as
+                    // if indent wasn't changed
+                    old_line_offsets.push(state.line_offsets[next_line].clone());
+                    state.line_offsets[next_line].indent_nonspace -= state.blk_indent as i32;
+                }
+
+                break;
+            }
+
+            old_line_offsets.push(state.line_offsets[next_line].clone());
+
+            // A negative indentation means that this is a paragraph continuation
+            //
+            state.line_offsets[next_line].indent_nonspace = -1;
+            next_line += 1;
+        }
+
+        let old_indent = state.blk_indent;
+        state.blk_indent = 0;
+
+        let old_node = std::mem::replace(&mut state.node, (self.f)());
+        let old_line_max = state.line_max;
+        state.line = start_line;
+        state.line_max = next_line;
+        state.level += 1;
+        state.md.block.tokenize(state);
+        state.level -= 1;
+        next_line = state.line;
+        state.line = start_line;
+        state.line_max = old_line_max;
+
+        // Restore original tShift; this might not be necessary since the parser
+        // has already been here, but just to make sure we can do that.
+        for (idx, line_offset) in old_line_offsets.iter_mut().enumerate() {
+            std::mem::swap(&mut state.line_offsets[idx + start_line], line_offset);
+        }
+        state.blk_indent = old_indent;
+
+        let node = std::mem::replace(&mut state.node, old_node);
+        Some((node, next_line - start_line))
+    }
+}
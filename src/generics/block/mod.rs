@@ -0,0 +1,6 @@
+//! Generic block-level structures.
+pub mod fenced_container;
+pub mod fenced_raw;
+pub mod line_prefix;
+
+mod fence_utils;
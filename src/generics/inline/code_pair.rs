@@ -5,8 +5,8 @@
 //! resulting in the same node).
 //!
 //! You add a custom structure by using [add_with] function, which takes following arguments:
-//!  - `MARKER` - marker character
 //!  - `md` - parser instance
+//!  - `marker` - marker character
 //!  - `f` - function that should return your custom [Node]
 //!
 //! Here is an example of a rule turning `%foo%` into `🦀foo🦀`:
@@ -26,7 +26,7 @@
 //! }
 //!
 //! let md = &mut MarkdownIt::new();
-//! code_pair::add_with::<'%'>(md, |_| Node::new(Ferris));
+//! code_pair::add_with(md, '%', |_| Node::new(Ferris));
 //! let html = md.parse("hello %world%").render();
 //! assert_eq!(html.trim(), "hello 🦀world🦀");
 //! ```
@@ -34,54 +34,71 @@
 //! This generic structure follows exact rules of code span in CommonMark:
 //!
 //! 1. Literal marker character sequence can be used inside of structure if its length
-//! doesn't match length of the opening/closing sequence (e.g. with `%` defined
-//! as a marker, `%%foo%bar%%` gets parsed as `Node("foo%bar")`).
+//!    doesn't match length of the opening/closing sequence (e.g. with `%` defined
+//!    as a marker, `%%foo%bar%%` gets parsed as `Node("foo%bar")`).
 //!
 //! 2. Single space inside is trimmed to allow you to write `% %%foo %` to be parsed as
-//! `Node("%%foo")`.
+//!    `Node("%%foo")`.
 //!
 //! If you define two structures with the same marker, only the first one will work.
 //!
-use crate::parser::extset::{InlineRootExt, MarkdownItExt};
-use crate::parser::inline::{InlineRule, InlineState, Text};
+use std::collections::HashMap;
+
+use crate::parser::core::RuleId;
+use crate::parser::extset::InlineRootExt;
+use crate::parser::inline::{InlineRule, InlineState, Text, Trigger};
 use crate::{MarkdownIt, Node};
 
 #[derive(Debug, Default)]
-struct CodePairCache<const MARKER: char> {
+struct CodePairCache(HashMap<char, CodePairCacheEntry>);
+impl InlineRootExt for CodePairCache {}
+
+#[derive(Debug, Default)]
+struct CodePairCacheEntry {
     scanned: bool,
     max: Vec<usize>,
 }
-impl<const MARKER: char> InlineRootExt for CodePairCache<MARKER> {}
 
-#[derive(Debug)]
-struct CodePairConfig<const MARKER: char>(fn (usize) -> Node);
-impl<const MARKER: char> MarkdownItExt for CodePairConfig<MARKER> {}
-
-pub fn add_with<const MARKER: char>(md: &mut MarkdownIt, f: fn (length: usize) -> Node) {
-    md.ext.insert(CodePairConfig::<MARKER>(f));
+pub fn add_with(md: &mut MarkdownIt, marker: char, f: impl Fn (usize) -> Node + Send + Sync + 'static) {
+    md.inline.add_rule_with_id(rule_id(marker), CodePairScanner::new(marker, f));
+}
 
-    md.inline.add_rule::<CodePairScanner<MARKER>>();
+/// Id of the inline rule that handles structures with given `marker`.
+pub fn rule_id(marker: char) -> RuleId {
+    format!("code_pair:{}", marker).into()
 }
 
 #[doc(hidden)]
-pub struct CodePairScanner<const MARKER: char>;
-impl<const MARKER: char> InlineRule for CodePairScanner<MARKER> {
-    const MARKER: char = MARKER;
+pub struct CodePairScanner {
+    marker: char,
+    f: Box<dyn Fn (usize) -> Node + Send + Sync>,
+}
+
+impl CodePairScanner {
+    pub(crate) fn new(marker: char, f: impl Fn (usize) -> Node + Send + Sync + 'static) -> Self {
+        Self { marker, f: Box::new(f) }
+    }
+}
+
+impl InlineRule for CodePairScanner {
+    fn trigger(&self) -> Trigger { Trigger::Char(self.marker) }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
+        let marker = self.marker;
         let mut chars = state.src[state.pos..state.pos_max].chars();
-        if chars.next().unwrap() != MARKER { return None; }
-        if state.trailing_text_get().ends_with(MARKER) { return None; }
+        if chars.next().unwrap() != marker { return None; }
+        if state.trailing_text_get().ends_with(marker) { return None; }
 
         let mut pos = state.pos + 1;
 
         // scan marker length
-        while Some(MARKER) == chars.next() {
+        while Some(marker) == chars.next() {
             pos += 1;
         }
 
         // backtick length => last seen position
-        let backticks = state.inline_ext.get_or_insert_default::<CodePairCache<MARKER>>();
+        let cache = state.inline_ext.get_or_insert_default::<CodePairCache>();
+        let backticks = cache.0.entry(marker).or_default();
         let opener_len = pos - state.pos;
 
         if backticks.scanned && backticks.max.get(opener_len).copied().unwrap_or(0) <= state.pos {
@@ -95,14 +112,14 @@ impl<const MARKER: char> InlineRule for CodePairScanner<MARKER> {
         let mut match_end = pos;
 
         // Nothing found in the cache, scan until the end of the line (or until marker is found)
-        while let Some(p) = state.src[match_end..state.pos_max].find(MARKER) {
+        while let Some(p) = state.src[match_end..state.pos_max].find(marker) {
             match_start = match_end + p;
 
             // scan marker length
             match_end = match_start + 1;
             chars = state.src[match_end..state.pos_max].chars();
 
-            while Some(MARKER) == chars.next() {
+            while Some(marker) == chars.next() {
                 match_end += 1;
             }
 
@@ -117,8 +134,7 @@ impl<const MARKER: char> InlineRule for CodePairScanner<MARKER> {
                     match_start -= 1;
                 }
 
-                let f = state.md.ext.get::<CodePairConfig<MARKER>>().unwrap().0;
-                let mut node = f(opener_len);
+                let mut node = (self.f)(opener_len);
 
                 let mut inner_node = Node::new(Text { content });
                 inner_node.srcmap = state.get_map(pos, match_start);
@@ -128,14 +144,15 @@ impl<const MARKER: char> InlineRule for CodePairScanner<MARKER> {
             }
 
             // Some different length found, put it in cache as upper limit of where closer can be found
-            let backticks = state.inline_ext.get_mut::<CodePairCache<MARKER>>().unwrap();
+            let cache = state.inline_ext.get_mut::<CodePairCache>().unwrap();
+            let backticks = cache.0.get_mut(&marker).unwrap();
             while backticks.max.len() <= closer_len { backticks.max.push(0); }
             backticks.max[closer_len] = match_start;
         }
 
         // Scanned through the end, didn't find anything
-        let backticks = state.inline_ext.get_mut::<CodePairCache<MARKER>>().unwrap();
-        backticks.scanned = true;
+        let cache = state.inline_ext.get_mut::<CodePairCache>().unwrap();
+        cache.0.get_mut(&marker).unwrap().scanned = true;
 
         None
     }
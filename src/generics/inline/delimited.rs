@@ -0,0 +1,243 @@
+//! Structure enclosed in arbitrary opening and closing strings, e.g. `[[wikilink]]`.
+//!
+//! It allows you to define a custom structure with multi-character delimiters
+//! which don't have to be the same on both sides (e.g. `{{ variable }}`,
+//! `{% shortcode %}` or `<<include>>`).
+//!
+//! You add a custom structure by using [add_with] function, which takes following arguments:
+//!  - `md` - parser instance
+//!  - `open` - opening string
+//!  - `close` - closing string
+//!  - `content` - whether content is kept verbatim ([Content::Raw]) or parsed
+//!    as inline markdown and added as children of your node ([Content::Parsed])
+//!  - `nested` - allow the same structure to be nested inside
+//!  - `f` - function that receives source text between delimiters and should return
+//!    your custom [Node] (or `None` if this text should be handled by other rules)
+//!
+//! Here is an example of a rule turning `{{ name }}` into a variable:
+//!
+//! ```rust
+//! use markdown_it::generics::inline::delimited::{self, Content};
+//! use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+//!
+//! #[derive(Debug)]
+//! struct Variable(String);
+//! impl NodeValue for Variable {
+//!     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+//!         fmt.open("var", &[]);
+//!         fmt.text(&self.0);
+//!         fmt.close("var");
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! delimited::add_with(md, "{{", "}}", Content::Raw, false, |content| {
+//!     let name = content.trim();
+//!     (!name.is_empty()).then(|| Node::new(Variable(name.to_owned())))
+//! });
+//!
+//! let html = md.parse("hello, {{ *user* }}!").render();
+//! assert_eq!(html.trim(), "<p>hello, <var>*user*</var>!</p>");
+//! ```
+//!
+//! With [Content::Parsed], closing string is searched for the same way as
+//! closing bracket of a link label, so it is ignored inside of code spans,
+//! autolinks and other inline structures, and it can be escaped with a backslash.
+//!
+//! If you define two structures with the same opening string, only the first one will work.
+//!
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::core::RuleId;
+use crate::parser::extset::InlineRootExt;
+use crate::parser::inline::{InlineRule, InlineState, Trigger};
+use crate::{MarkdownIt, Node};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Describes how text between delimiters is processed.
+pub enum Content {
+    /// Content is passed to your function as is, without any markdown processing.
+    Raw,
+    /// Content is parsed as inline markdown, resulting nodes are added as children.
+    Parsed,
+}
+
+/// Opening string => ((start, pos_max) => position of closing string).
+#[derive(Debug, Default)]
+struct DelimitedScanCache(HashMap<String, HashMap<(usize, usize), Option<usize>>>);
+impl InlineRootExt for DelimitedScanCache {}
+
+/// Opening strings of non-nested structures we're currently inside of.
+#[derive(Debug, Default)]
+struct DelimitedLevel(HashSet<String>);
+impl InlineRootExt for DelimitedLevel {}
+
+pub fn add_with(
+    md: &mut MarkdownIt,
+    open: impl Into<String>,
+    close: impl Into<String>,
+    content: Content,
+    nested: bool,
+    f: impl Fn (&str) -> Option<Node> + Send + Sync + 'static,
+) {
+    let open = open.into();
+    let close = close.into();
+    assert!(!open.is_empty() && !close.is_empty(), "delimiters must not be empty");
+
+    md.inline.add_rule_with_id(rule_id(&open), DelimitedScanner {
+        open,
+        close,
+        content,
+        nested,
+        f: Box::new(f),
+    });
+}
+
+/// Id of the inline rule that handles structures with given `open` string.
+pub fn rule_id(open: &str) -> RuleId {
+    format!("delimited:{}", open).into()
+}
+
+#[doc(hidden)]
+pub struct DelimitedScanner {
+    open: String,
+    close: String,
+    content: Content,
+    nested: bool,
+    f: Box<dyn Fn (&str) -> Option<Node> + Send + Sync>,
+}
+
+impl InlineRule for DelimitedScanner {
+    fn trigger(&self) -> Trigger {
+        let open = self.open.chars().next().unwrap();
+        let close = self.close.chars().next().unwrap();
+        if self.content == Content::Parsed && open != close {
+            // parser must stop at closing string when skipping nested tokens
+            Trigger::Chars(vec![open, close])
+        } else {
+            Trigger::Char(open)
+        }
+    }
+
+    fn check(&self, state: &mut InlineState) -> Option<usize> {
+        let end = self.find_end(state)?;
+        let content = &state.src[state.pos + self.open.len()..end];
+        (self.f)(content)?;
+        Some(end + self.close.len() - state.pos)
+    }
+
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
+        let end = self.find_end(state)?;
+        let start = state.pos;
+        let content_start = start + self.open.len();
+        let node = (self.f)(&state.src[content_start..end])?;
+        let len = end + self.close.len() - start;
+
+        if self.content == Content::Raw {
+            return Some((node, len));
+        }
+
+        let old_node = std::mem::replace(&mut state.node, node);
+        let max = state.pos_max;
+
+        if !self.nested {
+            let level = state.inline_ext.get_or_insert_default::<DelimitedLevel>();
+            level.0.insert(self.open.clone());
+        }
+
+        state.pos = content_start;
+        state.pos_max = end;
+        state.level += 1;
+        state.md.inline.tokenize(state);
+        state.level -= 1;
+        state.pos = start;
+        state.pos_max = max;
+
+        if !self.nested {
+            let level = state.inline_ext.get_or_insert_default::<DelimitedLevel>();
+            level.0.remove(&self.open);
+        }
+
+        let node = std::mem::replace(&mut state.node, old_node);
+        Some((node, len))
+    }
+}
+
+impl DelimitedScanner {
+    /// Find position of the closing string for a structure starting at current position.
+    fn find_end(&self, state: &mut InlineState) -> Option<usize> {
+        if !state.src[state.pos..state.pos_max].starts_with(&self.open) { return None; }
+
+        if !self.nested {
+            if let Some(level) = state.inline_ext.get::<DelimitedLevel>() {
+                if level.0.contains(&self.open) { return None; }
+            }
+        }
+
+        let key = (state.pos, state.pos_max);
+        if let Some(cache) = state.inline_ext.get::<DelimitedScanCache>() {
+            if let Some(&cached) = cache.0.get(&self.open).and_then(|c| c.get(&key)) {
+                return cached;
+            }
+        }
+
+        let result = match self.content {
+            Content::Raw => self.find_end_raw(state),
+            Content::Parsed => self.find_end_parsed(state),
+        };
+
+        let cache = state.inline_ext.get_or_insert_default::<DelimitedScanCache>();
+        cache.0.entry(self.open.clone()).or_default().insert(key, result);
+        result
+    }
+
+    fn find_end_raw(&self, state: &InlineState) -> Option<usize> {
+        let mut pos = state.pos + self.open.len();
+        let mut level = 1;
+
+        while pos < state.pos_max {
+            let rest = &state.src[pos..state.pos_max];
+            if rest.starts_with(&self.close) {
+                level -= 1;
+                if level == 0 { return Some(pos); }
+                pos += self.close.len();
+            } else if self.nested && rest.starts_with(&self.open) {
+                level += 1;
+                pos += self.open.len();
+            } else {
+                pos += rest.chars().next().unwrap().len_utf8();
+            }
+        }
+
+        None
+    }
+
+    fn find_end_parsed(&self, state: &mut InlineState) -> Option<usize> {
+        let old_pos = state.pos;
+        let mut result = None;
+
+        state.pos += self.open.len();
+
+        while state.pos < state.pos_max {
+            let rest = &state.src[state.pos..state.pos_max];
+            if rest.starts_with(&self.close) {
+                result = Some(state.pos);
+                break;
+            }
+
+            if !self.nested && rest.starts_with(&self.open) {
+                // nested structure isn't allowed, so treat its opening string as text
+                state.pos += self.open.len();
+                continue;
+            }
+
+            // if nesting is allowed, this will skip over entire nested structure
+            state.md.inline.skip_token(state);
+        }
+
+        // restore old state
+        state.pos = old_pos;
+        result
+    }
+}
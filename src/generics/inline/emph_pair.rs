@@ -12,12 +12,12 @@
 //!  - `^superscript^` -> `<sup>superscript</sup>`
 //!
 //! You add a custom structure by using [add_with] function, which takes following arguments:
-//!  - `MARKER` - marker character
-//!  - `LENGTH` - length of the opening/closing marker (can be 1, 2 or 3)
-//!  - `CAN_SPLIT_WORD` - whether this structure can be found in the middle of the word
+//!  - `md` - parser instance
+//!  - `marker` - marker character
+//!  - `length` - length of the opening/closing marker (can be 1, 2 or 3)
+//!  - `can_split_word` - whether this structure can be found in the middle of the word
 //!    (for example, note the difference between `foo*bar*baz` and `foo_bar_baz`
 //!    in CommonMark - first one is an emphasis, second one isn't)
-//!  - `md` - parser instance
 //!  - `f` - function that should return your custom [Node]
 //!
 //! Here is an example of implementing superscript in your custom code:
@@ -37,7 +37,7 @@
 //! }
 //!
 //! let md = &mut MarkdownIt::new();
-//! emph_pair::add_with::<'^', 1, true>(md, || Node::new(Superscript));
+//! emph_pair::add_with(md, '^', 1, true, || Node::new(Superscript));
 //!
 //! let html = md.parse("e^iπ^+1=0").render();
 //! assert_eq!(html.trim(), "e<sup>iπ</sup>+1=0");
@@ -47,24 +47,25 @@
 //! e.g. `` *foo`bar*baz` `` is parsed as `*foo<code>bar*baz</code>`.
 //!
 use std::cmp::min;
+use std::collections::HashMap;
 
 use crate::common::sourcemap::SourcePos;
-use crate::parser::core::CoreRule;
-use crate::parser::extset::{MarkdownItExt, NodeExt};
+use crate::parser::core::{CoreRule, Root, RuleId};
+use crate::parser::diagnostics::{Diagnostic, Diagnostics};
+use crate::parser::extset::NodeExt;
 use crate::parser::inline::builtin::InlineParserRule;
-use crate::parser::inline::{InlineRule, InlineState, Text};
+use crate::parser::inline::{InlineRule, InlineState, Text, Trigger};
 use crate::{MarkdownIt, Node, NodeValue};
 
-#[derive(Debug, Default)]
-struct PairConfig<const MARKER: char> {
-    inserted: bool,
-    fns: [Option<fn () -> Node>; 3],
-}
-impl<const MARKER: char> MarkdownItExt for PairConfig<MARKER> {}
+type NodeFn = Box<dyn Fn () -> Node + Send + Sync>;
 
-#[derive(Debug, Default)]
-struct OpenersBottom<const MARKER: char>([ usize; 6 ]);
-impl<const MARKER: char> NodeExt for OpenersBottom<MARKER> {}
+#[derive(Debug, Default, Clone)]
+struct OpenersBottom(HashMap<char, [ usize; 6 ]>);
+impl NodeExt for OpenersBottom {
+    fn clone_ext(&self) -> Option<Box<dyn NodeExt>> {
+        Some(Box::new(self.clone()))
+    }
+}
 
 #[derive(Debug, Clone)]
 #[doc(hidden)]
@@ -82,160 +83,196 @@ pub struct EmphMarker {
     // an emphasis.
     pub open:      bool,
     pub close:     bool,
+
+    // Shortest marker length that has a node function registered,
+    // shorter leftovers can't ever be matched (e.g. single `~` with `~~strikethrough~~`).
+    pub min_length: usize,
 }
 
 // this node is supposed to be replaced by actual emph or text node
-impl NodeValue for EmphMarker {}
-
-pub fn add_with<const MARKER: char, const LENGTH: u8, const CAN_SPLIT_WORD: bool>(md: &mut MarkdownIt, f: fn () -> Node) {
-    let pair_config = md.ext.get_or_insert_default::<PairConfig<MARKER>>();
-    pair_config.fns[LENGTH as usize - 1] = Some(f);
+impl NodeValue for EmphMarker {
+    crate::impl_clone_value!();
+}
 
-    if !pair_config.inserted {
-        pair_config.inserted = true;
-        md.inline.add_rule::<EmphPairScanner<MARKER, CAN_SPLIT_WORD>>();
+pub fn add_with(
+    md: &mut MarkdownIt,
+    marker: char,
+    length: u8,
+    can_split_word: bool,
+    f: impl Fn () -> Node + Send + Sync + 'static,
+) {
+    assert!((1..=3).contains(&length), "emph_pair marker length must be 1, 2 or 3");
+
+    let id = rule_id(marker);
+    if let Some(scanner) = md.inline.get_rule_mut::<EmphPairScanner>(id.clone()) {
+        scanner.fns[length as usize - 1] = Some(Box::new(f));
+    } else {
+        let mut scanner = EmphPairScanner {
+            marker,
+            can_split_word,
+            fns: Default::default(),
+        };
+        scanner.fns[length as usize - 1] = Some(Box::new(f));
+        md.inline.add_rule_with_id(id, scanner);
     }
 
     if !md.has_rule::<FragmentsJoin>() {
-        md.add_rule::<FragmentsJoin>()
+        md.add_rule(FragmentsJoin)
             .before_all()
             .after::<InlineParserRule>();
     }
 }
 
+/// Id of the inline rule that handles structures with given `marker`.
+pub fn rule_id(marker: char) -> RuleId {
+    format!("emph_pair:{}", marker).into()
+}
+
 #[doc(hidden)]
-pub struct EmphPairScanner<const MARKER: char, const CAN_SPLIT_WORD: bool>;
-impl<const MARKER: char, const CAN_SPLIT_WORD: bool> InlineRule for EmphPairScanner<MARKER, CAN_SPLIT_WORD> {
-    const MARKER: char = MARKER;
+pub struct EmphPairScanner {
+    marker: char,
+    can_split_word: bool,
+    fns: [Option<NodeFn>; 3],
+}
+
+impl InlineRule for EmphPairScanner {
+    fn trigger(&self) -> Trigger { Trigger::Char(self.marker) }
 
     // this rule works on a closing marker, so for technical reasons any rules trying to skip it
     // should see just plain text
-    fn check(_: &mut InlineState) -> Option<usize> { None }
+    fn check(&self, _: &mut InlineState) -> Option<usize> { None }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
-        if chars.next().unwrap() != MARKER { return None; }
+        if chars.next().unwrap() != self.marker { return None; }
 
-        let scanned = state.scan_delims(state.pos, CAN_SPLIT_WORD);
+        let scanned = state.scan_delims(state.pos, self.can_split_word);
         let mut node = Node::new(EmphMarker {
-            marker:    MARKER,
+            marker:    self.marker,
             length:    scanned.length,
             remaining: scanned.length,
             open:      scanned.can_open,
             close:     scanned.can_close,
+            min_length: self.fns.iter().position(Option::is_some).map_or(usize::MAX, |idx| idx + 1),
         });
         node.srcmap = state.get_map(state.pos, state.pos + scanned.length);
-        node = scan_and_match_delimiters::<MARKER>(state, node);
+        node = self.scan_and_match_delimiters(state, node);
         let map = node.srcmap.unwrap().get_byte_offsets();
         // backtrack to keep correct source maps
-        state.pos += scanned.length;
-        let token_len = map.1 - map.0;
-        state.pos -= token_len;
+        // (source positions are mapped back, because inline content may skip over
+        // line prefixes like `> ` which are present in the source but not here)
+        let end = state.pos + scanned.length;
+        let start = state.get_inline_pos_for(map.0).min(state.pos);
+        let token_len = end - start;
+        state.pos = start;
         Some((node, token_len))
     }
 }
 
-/// Assuming last token is a closing delimiter we just inserted,
-/// try to find opener(s). If any are found, move stuff to nested emph node.
-fn scan_and_match_delimiters<const MARKER: char>(state: &mut InlineState, mut closer_token: Node) -> Node {
-    if state.node.children.is_empty() { return closer_token; } // must have at least opener and closer
-
-    let mut closer = closer_token.cast_mut::<EmphMarker>().unwrap().clone();
-    if !closer.close { return closer_token; }
-
-    // Previously calculated lower bounds (previous fails)
-    // for each marker, each delimiter length modulo 3,
-    // and for whether this closer can be an opener;
-    // https://github.com/commonmark/cmark/commit/34250e12ccebdc6372b8b49c44fab57c72443460
-    let openers_for_marker = state.node.ext.get_or_insert_default::<OpenersBottom<MARKER>>();
-    let openers_parameter = (closer.open as usize) * 3 + closer.length % 3;
-
-    let min_opener_idx = openers_for_marker.0[openers_parameter];
-
-    let mut idx = state.node.children.len() - 1;
-    let mut new_min_opener_idx = idx;
-    while idx > min_opener_idx {
-        idx -= 1;
-
-        let Some(opener) = state.node.children[idx].cast::<EmphMarker>() else { continue; };
-
-        let mut opener = opener.clone();
-        if opener.open && opener.marker == closer.marker && !is_odd_match(&opener, &closer) {
-            while closer.remaining > 0 && opener.remaining > 0 {
-                let max_marker_len = min(3, min(opener.remaining, closer.remaining));
-                let mut matched_rule = None;
-                let fns = &state.md.ext.get::<PairConfig<MARKER>>().unwrap().fns;
-                for marker_len in (1..=max_marker_len).rev() {
-                    if let Some(f) = fns[marker_len-1] {
-                        matched_rule = Some((marker_len, f));
-                        break;
+impl EmphPairScanner {
+    /// Assuming last token is a closing delimiter we just inserted,
+    /// try to find opener(s). If any are found, move stuff to nested emph node.
+    fn scan_and_match_delimiters(&self, state: &mut InlineState, mut closer_token: Node) -> Node {
+        if state.node.children.is_empty() { return closer_token; } // must have at least opener and closer
+
+        let mut closer = closer_token.cast_mut::<EmphMarker>().unwrap().clone();
+        if !closer.close { return closer_token; }
+
+        // Previously calculated lower bounds (previous fails)
+        // for each marker, each delimiter length modulo 3,
+        // and for whether this closer can be an opener;
+        // https://github.com/commonmark/cmark/commit/34250e12ccebdc6372b8b49c44fab57c72443460
+        let openers_bottom = state.node.ext.get_or_insert_default::<OpenersBottom>();
+        let openers_for_marker = openers_bottom.0.entry(self.marker).or_default();
+        let openers_parameter = (closer.open as usize) * 3 + closer.length % 3;
+
+        let min_opener_idx = openers_for_marker[openers_parameter];
+
+        let mut idx = state.node.children.len() - 1;
+        let mut new_min_opener_idx = idx;
+        while idx > min_opener_idx {
+            idx -= 1;
+
+            let Some(opener) = state.node.children[idx].cast::<EmphMarker>() else { continue; };
+
+            let mut opener = opener.clone();
+            if opener.open && opener.marker == closer.marker && !is_odd_match(&opener, &closer) {
+                while closer.remaining > 0 && opener.remaining > 0 {
+                    let max_marker_len = min(3, min(opener.remaining, closer.remaining));
+                    let mut matched_rule = None;
+                    for marker_len in (1..=max_marker_len).rev() {
+                        if let Some(f) = &self.fns[marker_len-1] {
+                            matched_rule = Some((marker_len, f));
+                            break;
+                        }
                     }
-                }
 
-                // If matched_fn isn't found, it can only mean that function is defined for larger marker
-                // than we have (e.g. function defined for **, we have *).
-                // Treat this as "marker not found".
-                if matched_rule.is_none() { break; }
+                    // If matched_fn isn't found, it can only mean that function is defined for larger marker
+                    // than we have (e.g. function defined for **, we have *).
+                    // Treat this as "marker not found".
+                    if matched_rule.is_none() { break; }
 
-                let (marker_len, marker_fn) = matched_rule.unwrap();
+                    let (marker_len, marker_fn) = matched_rule.unwrap();
 
-                closer.remaining -= marker_len;
-                opener.remaining -= marker_len;
+                    closer.remaining -= marker_len;
+                    opener.remaining -= marker_len;
 
-                let mut new_token = marker_fn();
-                new_token.children = state.node.children.split_off(idx + 1);
+                    let mut new_token = marker_fn();
+                    new_token.children = state.node.children.split_off(idx + 1);
 
-                // cut marker_len chars from start, i.e. "12345" -> "345"
-                let mut end_map_pos = 0;
-                if let Some(map) = closer_token.srcmap {
-                    let (start, end) = map.get_byte_offsets();
-                    closer_token.srcmap = Some(SourcePos::new(start + marker_len, end));
-                    end_map_pos = start + marker_len;
-                }
+                    // cut marker_len chars from start, i.e. "12345" -> "345"
+                    let mut end_map_pos = 0;
+                    if let Some(map) = closer_token.srcmap {
+                        let (start, end) = map.get_byte_offsets();
+                        closer_token.srcmap = Some(SourcePos::new(start + marker_len, end));
+                        end_map_pos = start + marker_len;
+                    }
 
-                // cut marker_len chars from end, i.e. "12345" -> "123"
-                let mut start_map_pos = 0;
-                let opener_token = state.node.children.last_mut().unwrap();
-                if let Some(map) = opener_token.srcmap {
-                    let (start, end) = map.get_byte_offsets();
-                    opener_token.srcmap = Some(SourcePos::new(start, end - marker_len));
-                    start_map_pos = end - marker_len;
-                }
+                    // cut marker_len chars from end, i.e. "12345" -> "123"
+                    let mut start_map_pos = 0;
+                    let opener_token = state.node.children.last_mut().unwrap();
+                    if let Some(map) = opener_token.srcmap {
+                        let (start, end) = map.get_byte_offsets();
+                        opener_token.srcmap = Some(SourcePos::new(start, end - marker_len));
+                        start_map_pos = end - marker_len;
+                    }
 
-                new_token.srcmap = state.get_map(start_map_pos, end_map_pos);
+                    new_token.srcmap = Some(SourcePos::new(start_map_pos, end_map_pos));
 
-                // remove empty node as a small optimization so we can do less work later
-                if opener.remaining == 0 { state.node.children.pop(); }
+                    // remove empty node as a small optimization so we can do less work later
+                    if opener.remaining == 0 { state.node.children.pop(); }
 
-                new_min_opener_idx = 0;
-                state.node.children.push(new_token);
+                    new_min_opener_idx = 0;
+                    state.node.children.push(new_token);
 
+                }
             }
-        }
 
-        if opener.remaining > 0 {
-            state.node.children[idx].replace(opener);
-        } // otherwise node was already deleted
-    }
+            if opener.remaining > 0 {
+                state.node.children[idx].replace(opener);
+            } // otherwise node was already deleted
+        }
 
-    if new_min_opener_idx != 0 {
-        // If match for this delimiter run failed, we want to set lower bound for
-        // future lookups. This is required to make sure algorithm has linear
-        // complexity.
-        //
-        // See details here:
-        // https://github.com/commonmark/cmark/issues/178#issuecomment-270417442
-        //
-        let openers_for_marker = state.node.ext.get_or_insert_default::<OpenersBottom<MARKER>>();
-        openers_for_marker.0[openers_parameter] = new_min_opener_idx;
-    }
+        if new_min_opener_idx != 0 {
+            // If match for this delimiter run failed, we want to set lower bound for
+            // future lookups. This is required to make sure algorithm has linear
+            // complexity.
+            //
+            // See details here:
+            // https://github.com/commonmark/cmark/issues/178#issuecomment-270417442
+            //
+            let openers_bottom = state.node.ext.get_or_insert_default::<OpenersBottom>();
+            let openers_for_marker = openers_bottom.0.entry(self.marker).or_default();
+            openers_for_marker[openers_parameter] = new_min_opener_idx;
+        }
 
-    // remove empty node as a small optimization so we can do less work later
-    if closer.remaining > 0 {
-        closer_token.replace(closer);
-        closer_token
-    } else {
-        state.node.children.pop().unwrap()
+        // remove empty node as a small optimization so we can do less work later
+        if closer.remaining > 0 {
+            closer_token.replace(closer);
+            closer_token
+        } else {
+            state.node.children.pop().unwrap()
+        }
     }
 }
 
@@ -248,7 +285,7 @@ fn is_odd_match(opener: &EmphMarker, closer: &EmphMarker) -> bool {
     // closing delimiters must not be a multiple of 3 unless both lengths
     // are multiples of 3.
     //
-    #[allow(clippy::collapsible_if)]
+    #[allow(clippy::collapsible_if, clippy::manual_is_multiple_of)]
     if opener.close || closer.open {
         if (opener.length + closer.length) % 3 == 0 {
             if opener.length % 3 != 0 || closer.length % 3 != 0 {
@@ -264,8 +301,16 @@ fn is_odd_match(opener: &EmphMarker, closer: &EmphMarker) -> bool {
 #[doc(hidden)]
 pub struct FragmentsJoin;
 impl CoreRule for FragmentsJoin {
-    fn run(node: &mut Node, _: &MarkdownIt) {
-        node.walk_mut(|node, _| fragments_join(node));
+    fn run(&self, node: &mut Node, _: &MarkdownIt) {
+        let mut diagnostics = Vec::new();
+        node.walk_mut(|node, _| fragments_join(node, &mut diagnostics));
+
+        if !diagnostics.is_empty() {
+            if let Some(root) = node.cast_mut::<Root>() {
+                let list = root.ext.get_or_insert_default::<Diagnostics>();
+                diagnostics.into_iter().for_each(|d| list.push(d));
+            }
+        }
     }
 }
 
@@ -278,10 +323,19 @@ impl CoreRule for FragmentsJoin {
 /// leaves them as text (needed to merge with adjacent text) or turns them
 /// into opening/closing tags (which messes up levels inside).
 ///
-fn fragments_join(node: &mut Node) {
+fn fragments_join(node: &mut Node, diagnostics: &mut Vec<Diagnostic>) {
     // replace all emph markers with text tokens
     for token in node.children.iter_mut() {
         if let Some(data) = token.cast::<EmphMarker>() {
+            // markers that can't open or close anything (e.g. `2 * 3`) or are too short
+            // for any registered structure (e.g. `~` with strikethrough) are intended as text
+            if data.remaining >= data.min_length && (data.open || data.close) {
+                diagnostics.push(Diagnostic::warning(
+                    "unmatched-emphasis",
+                    format!("emphasis marker `{}` is not matched", data.marker.to_string().repeat(data.remaining)),
+                    token.srcmap,
+                ));
+            }
             let content = data.marker.to_string().repeat(data.remaining);
             token.replace(Text { content });
         }
@@ -8,84 +8,215 @@
 //! you come up with fun use case to add as an example!
 //!
 //! Add a custom structure by using [add_prefix] function, which takes following arguments:
-//!  - `PREFIX` - marker character before label (`!` in case of images)
-//!  - `ENABLE_NESTED` - allow nested links inside
 //!  - `md` - parser instance
-//!  - `f` - function that should return your custom [Node] given href and title
+//!  - `prefix` - marker character before label (`!` in case of images)
+//!  - `enable_nested` - allow nested links inside
+//!  - `f` - function that should return your custom [Node] given [LinkContext]
+//!    (which has href, title, link kind and reference label)
 //!
+//! ```rust
+//! use markdown_it::generics::inline::full_link::{self, LinkKind};
+//! use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+//!
+//! #[derive(Debug)]
+//! struct Footnote(String, LinkKind);
+//! impl NodeValue for Footnote {
+//!     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+//!         let kind = format!("{:?}", self.1).to_lowercase();
+//!         fmt.open("a", &[("href", self.0.clone()), ("class", kind)]);
+//!         fmt.contents(&node.children);
+//!         fmt.close("a");
+//!     }
+//! }
+//!
+//! let md = &mut MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! full_link::add_prefix(md, '~', false, |ctx| {
+//!     Node::new(Footnote(ctx.href.unwrap_or_default(), ctx.kind))
+//! });
+//!
+//! let html = md.parse("~[foo](/bar) ~[baz]\n\n[baz]: /quux").render();
+//! assert_eq!(html.trim(), r#"<p><a href="/bar" class="inline">foo</a> <a href="/quux" class="shortcut">baz</a></p>"#);
+//! ```
+//!
+use derivative::Derivative;
 use std::collections::HashMap;
 
-use crate::common::utils::unescape_all;
-use crate::parser::extset::{InlineRootExt, MarkdownItExt};
-use crate::parser::inline::{InlineRule, InlineState};
-use crate::plugins::cmark::block::reference::ReferenceMap;
+use crate::common::sourcemap::SourcePos;
+use crate::common::utils::{normalize_reference, unescape_all};
+use crate::parser::core::RuleId;
+use crate::parser::extset::{InlineRootExt, InlineRootExtSet, MarkdownItExt, RootExtSet};
+use crate::parser::inline::{InlineRule, InlineState, Trigger};
+use crate::plugins::cmark::block::reference::{ReferenceMap, ReferenceUsage};
 use crate::{MarkdownIt, Node};
 
+type LinkFn = Box<dyn Fn (LinkContext) -> Node + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Syntax form a link is written in.
+///
+/// Only forms produced by [full_link](self) are covered, autolinks
+/// (`<https://example.org>`) are out of scope and don't record a kind.
+pub enum LinkKind {
+    /// `[text](<href> "title")`
+    Inline,
+    /// `[text][label]`
+    Full,
+    /// `[label][]`
+    Collapsed,
+    /// `[label]`
+    Shortcut,
+}
+
+#[derive(Debug)]
+/// Everything known about a link when its node is created.
+pub struct LinkContext<'a> {
+    /// Syntax form of this link.
+    pub kind: LinkKind,
+    /// Link destination (already normalized).
+    pub href: Option<String>,
+    /// Link title.
+    pub title: Option<String>,
+    /// Source text between brackets, before any markdown is parsed in it.
+    pub text: String,
+    /// Label used to look up a reference definition (`None` for inline links),
+    /// as written by user, not normalized.
+    pub reference: Option<String>,
+    /// Parser instance.
+    pub md: &'a MarkdownIt,
+    /// Root extension set (e.g. to look up [ReferenceMap]).
+    pub root_ext: &'a mut RootExtSet,
+    /// Extension set of the current inline root.
+    pub inline_ext: &'a mut InlineRootExtSet,
+}
+
 #[derive(Debug)]
-struct LinkCfg<const PREFIX: char>(fn (Option<String>, Option<String>) -> Node);
-impl<const PREFIX: char> MarkdownItExt for LinkCfg<PREFIX> {}
+/// Reference link with a label that isn't defined, passed to the function
+/// set by [set_broken_reference_callback].
+pub struct BrokenReference<'a> {
+    /// Syntax form of this link (always one of reference forms).
+    pub kind: LinkKind,
+    /// Reference label as written by user, not normalized.
+    pub reference: &'a str,
+    /// Source position of the entire link.
+    pub srcmap: Option<SourcePos>,
+    /// Parser instance.
+    pub md: &'a MarkdownIt,
+    /// Root extension set (e.g. to report an error).
+    pub root_ext: &'a mut RootExtSet,
+}
+
+type BrokenReferenceFn = Box<dyn Fn (BrokenReference) -> Option<(String, Option<String>)> + Send + Sync>;
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct BrokenReferenceCallback(#[derivative(Debug = "ignore")] BrokenReferenceFn);
+impl MarkdownItExt for BrokenReferenceCallback {}
+
+/// Set a function that gets called when a reference link label (e.g. `ref` in `[text][ref]`)
+/// is not found among definitions. It may return destination and title to use instead,
+/// otherwise link is not created, and brackets are kept as plain text.
+///
+/// Returned destination is normalized and validated the same way as inline link
+/// destinations, so a link isn't created if it's rejected (e.g. `javascript:` urls).
+///
+/// It applies to all links and images (and any other [full_link](self) structures),
+/// and it is called at most once for each link.
+///
+/// ```rust
+/// use markdown_it::generics::inline::full_link;
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+/// full_link::set_broken_reference_callback(md, |broken| {
+///     broken.reference.strip_prefix("std::")
+///         .map(|name| (format!("https://doc.rust-lang.org/std/{}/", name), None))
+/// });
+///
+/// let html = md.parse("[std::vec] [other]").render();
+/// assert_eq!(html.trim(), r#"<p><a href="https://doc.rust-lang.org/std/vec/">std::vec</a> [other]</p>"#);
+/// ```
+pub fn set_broken_reference_callback(
+    md: &mut MarkdownIt,
+    f: impl Fn (BrokenReference) -> Option<(String, Option<String>)> + Send + Sync + 'static,
+) {
+    md.ext.insert(BrokenReferenceCallback(Box::new(f)));
+}
 
 /// adds custom rule with no prefix
-pub fn add<const ENABLE_NESTED: bool>(
+pub fn add(
     md: &mut MarkdownIt,
-    f: fn (url: Option<String>, title: Option<String>) -> Node
+    enable_nested: bool,
+    f: impl Fn (LinkContext) -> Node + Send + Sync + 'static,
 ) {
-    md.ext.insert(LinkCfg::<'\0'>(f));
-    md.inline.add_rule::<LinkScanner<ENABLE_NESTED>>();
+    md.inline.add_rule(LinkScanner { enable_nested, f: Box::new(f) });
     if !md.inline.has_rule::<LinkScannerEnd>() {
-        md.inline.add_rule::<LinkScannerEnd>();
+        md.inline.add_rule(LinkScannerEnd);
     }
 }
 
-/// adds custom rule with given `PREFIX` character
-pub fn add_prefix<const PREFIX: char, const ENABLE_NESTED: bool>(
+/// adds custom rule with given `prefix` character
+pub fn add_prefix(
     md: &mut MarkdownIt,
-    f: fn (url: Option<String>, title: Option<String>) -> Node
+    prefix: char,
+    enable_nested: bool,
+    f: impl Fn (LinkContext) -> Node + Send + Sync + 'static,
 ) {
-    md.ext.insert(LinkCfg::<PREFIX>(f));
-    md.inline.add_rule::<LinkPrefixScanner<PREFIX, ENABLE_NESTED>>();
+    md.inline.add_rule_with_id(rule_id(prefix), LinkPrefixScanner { prefix, enable_nested, f: Box::new(f) });
     if !md.inline.has_rule::<LinkScannerEnd>() {
-        md.inline.add_rule::<LinkScannerEnd>();
+        md.inline.add_rule(LinkScannerEnd);
     }
 }
 
+/// Id of the inline rule that handles structures with given `prefix`.
+pub fn rule_id(prefix: char) -> RuleId {
+    format!("full_link:{}", prefix).into()
+}
+
 #[doc(hidden)]
-pub struct LinkScanner<const ENABLE_NESTED: bool>;
-impl<const ENABLE_NESTED: bool> InlineRule for LinkScanner<ENABLE_NESTED> {
-    const MARKER: char = '[';
+pub struct LinkScanner {
+    enable_nested: bool,
+    f: LinkFn,
+}
+
+impl InlineRule for LinkScanner {
+    fn trigger(&self) -> Trigger { Trigger::Char('[') }
 
-    fn check(state: &mut InlineState) -> Option<usize> {
+    fn check(&self, state: &mut InlineState) -> Option<usize> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '[' { return None; }
-        rule_check(state, ENABLE_NESTED, 0)
+        rule_check(state, self.enable_nested, 0)
     }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '[' { return None; }
-        let f = state.md.ext.get::<LinkCfg<'\0'>>().unwrap().0;
-        rule_run(state, ENABLE_NESTED, 0, f)
+        rule_run(state, self.enable_nested, 0, &self.f)
     }
 }
 
 #[doc(hidden)]
-pub struct LinkPrefixScanner<const PREFIX: char, const ENABLE_NESTED: bool>;
-impl<const PREFIX: char, const ENABLE_NESTED: bool> InlineRule for LinkPrefixScanner<PREFIX, ENABLE_NESTED> {
-    const MARKER: char = PREFIX;
+pub struct LinkPrefixScanner {
+    prefix: char,
+    enable_nested: bool,
+    f: LinkFn,
+}
 
-    fn check(state: &mut InlineState) -> Option<usize> {
+impl InlineRule for LinkPrefixScanner {
+    fn trigger(&self) -> Trigger { Trigger::Char(self.prefix) }
+
+    fn check(&self, state: &mut InlineState) -> Option<usize> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
-        if chars.next() != Some(PREFIX) { return None; }
+        if chars.next() != Some(self.prefix) { return None; }
         if chars.next() != Some('[') { return None; }
-        rule_check(state, ENABLE_NESTED, 1)
+        rule_check(state, self.enable_nested, 1)
     }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
-        if chars.next() != Some(PREFIX) { return None; }
+        if chars.next() != Some(self.prefix) { return None; }
         if chars.next() != Some('[') { return None; }
-        let f = state.md.ext.get::<LinkCfg<PREFIX>>().unwrap().0;
-        rule_run(state, ENABLE_NESTED, 1, f)
+        rule_run(state, self.enable_nested, 1, &self.f)
     }
 }
 
@@ -94,10 +225,10 @@ impl<const PREFIX: char, const ENABLE_NESTED: bool> InlineRule for LinkPrefixSca
 /// but it actually doesn't do anything
 pub struct LinkScannerEnd;
 impl InlineRule for LinkScannerEnd {
-    const MARKER: char = ']';
+    fn trigger(&self) -> Trigger { Trigger::Char(']') }
 
-    fn check(_: &mut InlineState) -> Option<usize> { None }
-    fn run(_: &mut InlineState) -> Option<(Node, usize)> { None }
+    fn check(&self, _: &mut InlineState) -> Option<usize> { None }
+    fn run(&self, _: &mut InlineState) -> Option<(Node, usize)> { None }
 }
 
 fn rule_check(state: &mut InlineState, enable_nested: bool, offset: usize) -> Option<usize> {
@@ -112,7 +243,7 @@ fn rule_run(
     state: &mut InlineState,
     enable_nested: bool,
     offset: usize,
-    f: fn (Option<String>, Option<String>) -> Node
+    f: &LinkFn,
 ) -> Option<(Node, usize)> {
     let start = state.pos;
     let result = parse_link(state, state.pos + offset, enable_nested)?;
@@ -121,23 +252,42 @@ fn rule_run(
     // We found the end of the link, and know for a fact it's a valid link;
     // so all that's left to do is to call tokenizer.
     //
-    let old_node = std::mem::replace(&mut state.node, f(result.href, result.title));
+    if let Some(reference) = &result.reference {
+        let srcmap = state.get_map(start, result.end);
+        state.root_ext.get_or_insert_default::<ReferenceUsage>().add(reference, srcmap);
+    }
+
+    let node = f(LinkContext {
+        kind: result.kind,
+        href: result.href,
+        title: result.title,
+        text: state.src[result.label_start..result.label_end].to_owned(),
+        reference: result.reference,
+        md: state.md,
+        root_ext: state.root_ext,
+        inline_ext: state.inline_ext,
+    });
+    let old_node = std::mem::replace(&mut state.node, node);
     let max = state.pos_max;
 
     state.link_level += 1;
+    state.level += 1;
     state.pos = result.label_start;
     state.pos_max = result.label_end;
     state.md.inline.tokenize(state);
     state.pos = start;
     state.pos_max = max;
+    state.level -= 1;
     state.link_level -= 1;
 
     let node = std::mem::replace(&mut state.node, old_node);
     Some((node, result.end - state.pos))
 }
 
+// (start, pos_max, enable_nested) => label end; the same label may be scanned
+// with a different `pos_max` when it is nested in another structure
 #[derive(Debug, Default)]
-struct LinkLabelScanCache(HashMap<(usize, bool), Option<usize>>);
+struct LinkLabelScanCache(HashMap<(usize, usize, bool), Option<usize>>);
 impl InlineRootExt for LinkLabelScanCache {}
 
 
@@ -147,7 +297,7 @@ impl InlineRootExt for LinkLabelScanCache {}
 // returns the end of the label
 fn parse_link_label(state: &mut InlineState, start: usize, enable_nested: bool) -> Option<usize> {
     let cache = state.inline_ext.get_or_insert_default::<LinkLabelScanCache>();
-    if let Some(&cached) = cache.0.get(&(start, enable_nested)) {
+    if let Some(&cached) = cache.0.get(&(start, state.pos_max, enable_nested)) {
         return cached;
     }
 
@@ -175,7 +325,7 @@ fn parse_link_label(state: &mut InlineState, start: usize, enable_nested: bool)
                 level += 1;
 
                 let cache = state.inline_ext.get_or_insert_default::<LinkLabelScanCache>();
-                if let Some(&cached) = cache.0.get(&(prev_pos, enable_nested)) {
+                if let Some(&cached) = cache.0.get(&(prev_pos, state.pos_max, enable_nested)) {
                     // maybe cache appeared as a result of skip_token
                     if let Some(cached_pos) = cached {
                         state.pos = cached_pos;
@@ -198,7 +348,7 @@ fn parse_link_label(state: &mut InlineState, start: usize, enable_nested: bool)
     state.pos = old_pos;
 
     let cache = state.inline_ext.get_or_insert_default::<LinkLabelScanCache>();
-    cache.0.insert((start, enable_nested), label_end);
+    cache.0.insert((start, state.pos_max, enable_nested), label_end);
 
     label_end
 }
@@ -328,6 +478,8 @@ pub fn parse_link_title(str: &str, start: usize, max: usize) -> Option<ParseLink
 }
 
 struct ParseLinkResult {
+    pub kind: LinkKind,
+    pub reference: Option<String>,
     pub label_start: usize,
     pub label_end: usize,
     pub href: Option<String>,
@@ -390,6 +542,8 @@ fn parse_link(state: &mut InlineState, pos: usize, enable_nested: bool) -> Optio
 
         if let Some(')') = state.src[pos..state.pos_max].chars().next() {
             return Some(ParseLinkResult {
+                kind: LinkKind::Inline,
+                reference: None,
                 label_start,
                 label_end,
                 href,
@@ -418,23 +572,70 @@ fn parse_link(state: &mut InlineState, pos: usize, enable_nested: bool) -> Optio
         _ => pos = label_end + 1,
     }
 
-    let references = state.root_ext.get::<ReferenceMap>()?;
-
     // covers label === '' and label === undefined
     // (collapsed reference link and shortcut reference link respectively)
-    let label = if matches!(maybe_label, None | Some("")) {
-        &state.src[label_start..label_end]
-    } else {
-        maybe_label.unwrap()
+    let (kind, label) = match maybe_label {
+        None => (LinkKind::Shortcut, &state.src[label_start..label_end]),
+        Some("") => (LinkKind::Collapsed, &state.src[label_start..label_end]),
+        Some(label) => (LinkKind::Full, label),
     };
+    let label = label.to_owned();
 
-    let (destination, title) = references.get(label)?;
+    let reference = state.root_ext.get::<ReferenceMap>()
+        .and_then(|references| references.get(&label))
+        .map(|(destination, title)| (destination.to_owned(), title.map(|s| s.to_owned())));
+
+    let (destination, title) = match reference {
+        Some(reference) => reference,
+        None => resolve_broken_reference(state, kind, &label, pos)?,
+    };
 
     Some(ParseLinkResult {
+        kind,
+        reference: Some(label),
         label_start,
         label_end,
-        href: Some(destination.to_owned()),
-        title: title.map(|s| s.to_owned()),
+        href: Some(destination),
+        title,
         end: pos,
     })
 }
+
+#[derive(Debug, Default)]
+// (start, end) => result of broken reference callback, so it's only called once for each link
+struct BrokenReferenceCache(HashMap<(usize, usize), Option<(String, Option<String>)>>);
+impl InlineRootExt for BrokenReferenceCache {}
+
+fn resolve_broken_reference(
+    state: &mut InlineState,
+    kind: LinkKind,
+    label: &str,
+    end: usize,
+) -> Option<(String, Option<String>)> {
+    let callback = state.md.ext.get::<BrokenReferenceCallback>()?;
+    if normalize_reference(label).is_empty() { return None; }
+
+    let key = (state.pos, end);
+    let cache = state.inline_ext.get_or_insert_default::<BrokenReferenceCache>();
+    if let Some(cached) = cache.0.get(&key) {
+        return cached.clone();
+    }
+
+    let srcmap = state.get_map(state.pos, end);
+    let result = (callback.0)(BrokenReference {
+        kind,
+        reference: label,
+        srcmap,
+        md: state.md,
+        root_ext: state.root_ext,
+    }).and_then(|(destination, title)| {
+        // same checks as for inline link destinations, invalid ones are dropped
+        let href = state.md.link_formatter.normalize_link(&destination);
+        state.md.link_formatter.validate_link(&href)?;
+        Some((href, title))
+    });
+
+    let cache = state.inline_ext.get_or_insert_default::<BrokenReferenceCache>();
+    cache.0.insert(key, result.clone());
+    result
+}
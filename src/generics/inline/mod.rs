@@ -1,4 +1,5 @@
 //! Generic inline-level structures.
 pub mod code_pair;
+pub mod delimited;
 pub mod emph_pair;
 pub mod full_link;
@@ -3,10 +3,12 @@
 //! Some markdown structures are very similar under the hood, for example:
 //!  - `*emphasis*`, `^supertext^` and `~~strikethrough~~`
 //!  - `[link]()` and `![image]()`
+//!  - `> blockquotes` and other blocks with a marker on every line
 //!
 //! In order to reuse the code between all those, a notion of generic
 //! markdown structures was created. If you want to use syntax like
 //! `=this=` or `++that++`, you only need to specify a character marker
 //! and a renderer function, these rules will figure out the rest.
 //!
+pub mod block;
 pub mod inline;
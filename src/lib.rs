@@ -21,6 +21,7 @@
 pub mod common;
 pub mod examples;
 pub mod generics;
+pub mod lint;
 pub mod parser;
 pub mod plugins;
 
@@ -0,0 +1,273 @@
+//! Style checks over the syntax tree, similar to `markdownlint`.
+//!
+//! [Linter] runs a set of [LintRule]s over a parsed document and its source,
+//! and returns [Diagnostics] with all issues found.
+//!
+//! ```rust
+//! use markdown_it::lint::{rules, Linter};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let mut linter = Linter::new();
+//! linter.get_mut::<rules::LineLength>().unwrap().max = 20;
+//! linter.remove("single-h1");
+//!
+//! let source = "# foo\n### bar\n\nthis line is too long to be accepted\n";
+//! let diagnostics = linter.lint(&md.parse(source), source);
+//! let codes: Vec<_> = diagnostics.iter().map(|d| d.code.as_ref()).collect();
+//! assert_eq!(codes, ["heading-increment", "line-length"]);
+//! ```
+//!
+//! Rules can be disabled in the document itself using html comments
+//! (`markdownlint-` prefix is also accepted instead of `lint-`):
+//!  - `<!-- lint-disable rule1 rule2 -->` - disable rules until `lint-enable`
+//!  - `<!-- lint-enable rule1 rule2 -->` - enable rules again
+//!  - `<!-- lint-disable-line rule1 -->` - disable rules for the current line
+//!  - `<!-- lint-disable-next-line rule1 -->` - disable rules for the next line
+//!
+//! If no rules are listed, all rules are disabled (or enabled). Rules are referred to
+//! by their name (e.g. `line-length`) or by markdownlint id (e.g. `MD013`).
+//!
+use downcast_rs::{impl_downcast, Downcast};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::common::sourcemap::SourcePos;
+use crate::parser::diagnostics::{Diagnostic, Diagnostics, Severity};
+use crate::Node;
+
+pub mod rules;
+
+/// Each lint rule must implement this trait.
+pub trait LintRule : Downcast + Send + Sync {
+    /// Name used in diagnostics, configuration and disable comments, e.g. `line-length`.
+    fn code(&self) -> &'static str;
+
+    /// Alternative names of this rule (e.g. `MD013` for compatibility with markdownlint).
+    fn aliases(&self) -> &'static [&'static str] { &[] }
+
+    /// Check the document and report found issues with [LintContext::report].
+    fn check(&self, ctx: &mut LintContext);
+}
+
+impl_downcast!(LintRule);
+
+/// Document being checked, passed to [LintRule::check].
+pub struct LintContext<'a> {
+    /// Root of the syntax tree.
+    pub root: &'a Node,
+    /// Markdown source the tree was parsed from.
+    pub source: &'a str,
+    code: &'static str,
+    severity: Severity,
+    found: &'a mut Vec<Diagnostic>,
+}
+
+impl LintContext<'_> {
+    /// Report an issue at a given position in the source.
+    pub fn report(&mut self, message: impl Into<String>, srcmap: Option<SourcePos>) {
+        self.found.push(Diagnostic::new(self.severity, self.code, message, srcmap));
+    }
+
+    /// Iterate over source lines (without line terminators) along with their byte offsets.
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        let source = self.source;
+        source.split_inclusive('\n').scan(0, move |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end_matches(['\n', '\r'])))
+        })
+    }
+}
+
+/// Set of lint rules with their configuration.
+pub struct Linter {
+    rules: Vec<(Box<dyn LintRule>, Severity)>,
+}
+
+impl Linter {
+    /// Create a linter with all [built-in rules](rules) in their default configuration.
+    pub fn new() -> Self {
+        let mut linter = Self::empty();
+        rules::add(&mut linter);
+        linter
+    }
+
+    /// Create a linter without any rules.
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Add a rule (replacing an existing one with the same code), reported as warning.
+    pub fn add(&mut self, rule: impl LintRule) {
+        self.remove(rule.code());
+        self.rules.push((Box::new(rule), Severity::Warning));
+    }
+
+    /// Remove a rule by its code or alias.
+    pub fn remove(&mut self, code: &str) {
+        self.rules.retain(|(rule, _)| !matches_code(rule.as_ref(), code));
+    }
+
+    /// Get mutable access to a rule to change its configuration.
+    pub fn get_mut<T: LintRule>(&mut self) -> Option<&mut T> {
+        self.rules.iter_mut().find_map(|(rule, _)| rule.downcast_mut::<T>())
+    }
+
+    /// Change severity of issues reported by a rule with given code or alias,
+    /// returns `false` if rule is not found.
+    pub fn set_severity(&mut self, code: &str, severity: Severity) -> bool {
+        let mut found = false;
+        for (rule, rule_severity) in self.rules.iter_mut() {
+            if matches_code(rule.as_ref(), code) {
+                *rule_severity = severity;
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Returns `true` if rule with given code or alias is enabled.
+    pub fn has_rule(&self, code: &str) -> bool {
+        self.rules.iter().any(|(rule, _)| matches_code(rule.as_ref(), code))
+    }
+
+    /// Codes of all enabled rules.
+    pub fn codes(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.rules.iter().map(|(rule, _)| rule.code())
+    }
+
+    /// Run all rules over the document `root` parsed from `source`.
+    pub fn lint(&self, root: &Node, source: &str) -> Diagnostics {
+        let suppressions = Suppressions::new(source);
+        let mut result = Diagnostics::new();
+
+        for (rule, severity) in self.rules.iter() {
+            let mut found = Vec::new();
+            rule.check(&mut LintContext {
+                root,
+                source,
+                code: rule.code(),
+                severity: *severity,
+                found: &mut found,
+            });
+
+            for diagnostic in found {
+                let offset = diagnostic.srcmap.map(|pos| pos.get_byte_offsets().0).unwrap_or_default();
+                if !suppressions.is_disabled(rule.as_ref(), offset) {
+                    result.push(diagnostic);
+                }
+            }
+        }
+
+        result.sort();
+        result
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Linter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Linter")
+            .field("rules", &self.codes().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+fn matches_code(rule: &dyn LintRule, code: &str) -> bool {
+    rule.code().eq_ignore_ascii_case(code) ||
+        rule.aliases().iter().any(|alias| alias.eq_ignore_ascii_case(code))
+}
+
+static COMMENT_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"<!--\s*(?:markdownlint|lint)-(disable-next-line|disable-line|disable|enable)((?:\s+[\w-]+)*)\s*-->").unwrap()
+});
+
+#[derive(Debug, Default, Clone)]
+/// Rules disabled at some point in the document.
+struct Disabled {
+    all: bool,
+    rules: HashSet<String>,
+}
+
+impl Disabled {
+    fn update(&mut self, rules: &str, disable: bool) {
+        let rules: Vec<_> = rules.split_whitespace().map(|s| s.to_ascii_lowercase()).collect();
+        if rules.is_empty() {
+            self.all = disable;
+            self.rules.clear();
+        } else if disable {
+            self.rules.extend(rules);
+        } else {
+            // enabling a single rule after `lint-disable` for all rules is not supported
+            rules.iter().for_each(|rule| { self.rules.remove(rule); });
+        }
+    }
+
+    fn contains(&self, rule: &dyn LintRule) -> bool {
+        self.all || self.rules.contains(&rule.code().to_ascii_lowercase()) ||
+            rule.aliases().iter().any(|alias| self.rules.contains(&alias.to_ascii_lowercase()))
+    }
+}
+
+/// Rules disabled for each line by inline comments.
+struct Suppressions {
+    line_starts: Vec<usize>,
+    lines: Vec<Disabled>,
+}
+
+impl Suppressions {
+    fn new(source: &str) -> Self {
+        let mut line_starts = Vec::new();
+        let mut lines = Vec::new();
+
+        if !source.contains("<!--") {
+            return Self { line_starts, lines };
+        }
+
+        let mut current = Disabled::default();
+        let mut next_line = Disabled::default();
+
+        let mut offset = 0;
+        for line in source.split_inclusive('\n') {
+            let mut this_line = std::mem::take(&mut next_line);
+
+            for captures in COMMENT_RE.captures_iter(line) {
+                let rules = &captures[2];
+                match &captures[1] {
+                    "disable" => current.update(rules, true),
+                    "enable" => current.update(rules, false),
+                    "disable-line" => this_line.update(rules, true),
+                    "disable-next-line" => next_line.update(rules, true),
+                    _ => unreachable!(),
+                }
+            }
+
+            this_line.all |= current.all;
+            this_line.rules.extend(current.rules.iter().cloned());
+
+            line_starts.push(offset);
+            lines.push(this_line);
+            offset += line.len();
+        }
+
+        Self { line_starts, lines }
+    }
+
+    fn is_disabled(&self, rule: &dyn LintRule, offset: usize) -> bool {
+        if self.lines.is_empty() { return false; }
+
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(x) => x,
+            Err(x) => x - 1,
+        };
+        self.lines[line].contains(rule)
+    }
+}
@@ -0,0 +1,31 @@
+//! Heading levels should only increment by one level at a time (MD001).
+//!
+//! `# h1` followed by `### h3` is reported, since `## h2` is skipped.
+use crate::lint::{LintContext, LintRule};
+
+use super::heading_level;
+
+#[derive(Debug)]
+pub struct HeadingIncrement;
+
+impl LintRule for HeadingIncrement {
+    fn code(&self) -> &'static str { "heading-increment" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD001", "header-increment"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut prev_level = None;
+        let mut found = Vec::new();
+
+        ctx.root.walk(|node, _| {
+            let Some(level) = heading_level(node) else { return; };
+            if let Some(prev) = prev_level {
+                if level > prev + 1 {
+                    found.push((format!("expected h{} after h{}, found h{}", prev + 1, prev, level), node.srcmap));
+                }
+            }
+            prev_level = Some(level);
+        });
+
+        found.into_iter().for_each(|(message, srcmap)| ctx.report(message, srcmap));
+    }
+}
@@ -0,0 +1,59 @@
+//! Lines should not be too long (MD013).
+//!
+//! Lines that only exceed the limit with a single word (e.g. a long url)
+//! are allowed, since they can't be wrapped anyway.
+use crate::common::sourcemap::SourcePos;
+use crate::lint::{LintContext, LintRule};
+use crate::plugins::cmark::block::code::CodeBlock;
+use crate::plugins::cmark::block::fence::CodeFence;
+
+use super::heading_level;
+
+#[derive(Debug)]
+pub struct LineLength {
+    /// Maximum number of characters in a line.
+    pub max: usize,
+    /// Check lines in code blocks.
+    pub code_blocks: bool,
+    /// Check lines in headings.
+    pub headings: bool,
+}
+
+impl Default for LineLength {
+    fn default() -> Self {
+        Self { max: 80, code_blocks: true, headings: true }
+    }
+}
+
+impl LintRule for LineLength {
+    fn code(&self) -> &'static str { "line-length" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD013"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut excluded = Vec::new();
+        ctx.root.walk(|node, _| {
+            let skip = (!self.code_blocks && (node.is::<CodeBlock>() || node.is::<CodeFence>())) ||
+                (!self.headings && heading_level(node).is_some());
+            if let (true, Some(map)) = (skip, node.srcmap) {
+                excluded.push(map.get_byte_offsets());
+            }
+        });
+
+        let mut found = Vec::new();
+
+        for (offset, line) in ctx.lines() {
+            if excluded.iter().any(|&(start, end)| offset + line.len() > start && offset < end) { continue; }
+
+            let Some((limit, _)) = line.char_indices().nth(self.max) else { continue; };
+            if !line[limit..].contains([' ', '\t']) { continue; }
+
+            let len = line.chars().count();
+            found.push((format!("line is {} characters long, maximum is {}", len, self.max),
+                SourcePos::new(offset + limit, offset + line.len())));
+        }
+
+        for (message, srcmap) in found {
+            ctx.report(message, Some(srcmap));
+        }
+    }
+}
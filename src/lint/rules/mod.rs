@@ -0,0 +1,53 @@
+//! Built-in lint rules.
+//!
+//! | name                 | markdownlint | description                                  |
+//! |----------------------|--------------|----------------------------------------------|
+//! | `heading-increment`  | MD001        | heading levels increase by one at a time     |
+//! | `ul-style`           | MD004        | consistent bullet list markers               |
+//! | `no-trailing-spaces` | MD009        | no whitespace at the end of lines            |
+//! | `line-length`        | MD013        | lines are not too long                       |
+//! | `single-h1`          | MD025        | only one top-level heading in the document   |
+//! | `no-bare-urls`       | MD034        | urls are wrapped in `<>` or written as links |
+//! | `no-alt-text`        | MD045        | images have alternate text                   |
+//!
+use crate::plugins::cmark::block::heading::ATXHeading;
+use crate::plugins::cmark::block::lheading::SetextHeader;
+use crate::Node;
+
+use super::Linter;
+
+pub mod heading_increment;
+pub mod line_length;
+pub mod no_alt_text;
+pub mod no_bare_urls;
+pub mod no_trailing_spaces;
+pub mod single_h1;
+pub mod ul_style;
+
+pub use heading_increment::HeadingIncrement;
+pub use line_length::LineLength;
+pub use no_alt_text::NoAltText;
+pub use no_bare_urls::NoBareUrls;
+pub use no_trailing_spaces::NoTrailingSpaces;
+pub use single_h1::SingleH1;
+pub use ul_style::UlStyle;
+
+/// Add all built-in rules with default configuration.
+pub fn add(linter: &mut Linter) {
+    linter.add(HeadingIncrement);
+    linter.add(UlStyle::default());
+    linter.add(NoTrailingSpaces::default());
+    linter.add(LineLength::default());
+    linter.add(SingleH1);
+    linter.add(NoBareUrls);
+    linter.add(NoAltText);
+}
+
+/// Level of ATX or Setext heading.
+fn heading_level(node: &Node) -> Option<u8> {
+    if let Some(heading) = node.cast::<ATXHeading>() {
+        Some(heading.level)
+    } else {
+        node.cast::<SetextHeader>().map(|heading| heading.level)
+    }
+}
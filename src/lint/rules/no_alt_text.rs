@@ -0,0 +1,25 @@
+//! Images should have alternate text (MD045).
+use crate::lint::{LintContext, LintRule};
+use crate::plugins::cmark::inline::image::Image;
+
+#[derive(Debug)]
+pub struct NoAltText;
+
+impl LintRule for NoAltText {
+    fn code(&self) -> &'static str { "no-alt-text" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD045"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut found = Vec::new();
+
+        ctx.root.walk(|node, _| {
+            if node.is::<Image>() && node.collect_text().trim().is_empty() {
+                found.push(node.srcmap);
+            }
+        });
+
+        for srcmap in found {
+            ctx.report("image has no alternate text", srcmap);
+        }
+    }
+}
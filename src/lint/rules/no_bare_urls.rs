@@ -0,0 +1,70 @@
+//! Urls should be written as links or autolinks (MD034).
+//!
+//! Text like `https://example.org` is not a link in CommonMark, so it should be
+//! written as `<https://example.org>`. Urls converted to links by `linkify`
+//! plugin are reported as well.
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::common::sourcemap::SourcePos;
+use crate::lint::{LintContext, LintRule};
+use crate::parser::inline::Text;
+use crate::plugins::cmark::block::code::CodeBlock;
+use crate::plugins::cmark::block::fence::CodeFence;
+use crate::plugins::cmark::inline::autolink::Autolink;
+use crate::plugins::cmark::inline::backticks::CodeInline;
+use crate::plugins::cmark::inline::link::Link;
+use crate::plugins::html::html_block::HtmlBlock;
+use crate::plugins::html::html_inline::HtmlInline;
+use crate::Node;
+
+static URL_RE : Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:https?|ftp)://[^\s<>]*[^\s<>.,;:!?'\x22)\]]").unwrap()
+});
+
+#[derive(Debug)]
+pub struct NoBareUrls;
+
+impl LintRule for NoBareUrls {
+    fn code(&self) -> &'static str { "no-bare-urls" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD034"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut found = Vec::new();
+        find_bare_urls(ctx.root, ctx.source, &mut found);
+
+        for (url, srcmap) in found {
+            ctx.report(format!("bare url `{}`, use `<{}>` instead", url, url), srcmap);
+        }
+    }
+}
+
+fn find_bare_urls(node: &Node, source: &str, found: &mut Vec<(String, Option<SourcePos>)>) {
+    #[cfg(feature = "linkify")]
+    if let Some(link) = node.cast::<crate::plugins::extra::linkify::Linkified>() {
+        found.push((link.url.clone(), node.srcmap));
+        return;
+    }
+
+    if node.is::<Link>() || node.is::<Autolink>() || node.is::<CodeInline>() ||
+       node.is::<CodeBlock>() || node.is::<CodeFence>() ||
+       node.is::<HtmlInline>() || node.is::<HtmlBlock>() {
+        return;
+    }
+
+    if let Some(text) = node.cast::<Text>() {
+        for url in URL_RE.find_iter(&text.content) {
+            // locate url in the source if possible, fall back to the entire text node
+            let srcmap = node.srcmap.and_then(|map| {
+                let (start, end) = map.get_byte_offsets();
+                let pos = source.get(start..end)?.find(url.as_str())? + start;
+                Some(SourcePos::new(pos, pos + url.len()))
+            }).or(node.srcmap);
+            found.push((url.as_str().to_owned(), srcmap));
+        }
+    }
+
+    for child in node.children.iter() {
+        find_bare_urls(child, source, found);
+    }
+}
@@ -0,0 +1,43 @@
+//! Lines should not end with whitespace (MD009).
+//!
+//! Exactly [NoTrailingSpaces::br_spaces] spaces are allowed after text,
+//! since that's how hard line breaks are written.
+use crate::common::sourcemap::SourcePos;
+use crate::lint::{LintContext, LintRule};
+
+#[derive(Debug)]
+pub struct NoTrailingSpaces {
+    /// Number of spaces allowed for a hard line break (0 to disallow).
+    pub br_spaces: usize,
+}
+
+impl Default for NoTrailingSpaces {
+    fn default() -> Self {
+        Self { br_spaces: 2 }
+    }
+}
+
+impl LintRule for NoTrailingSpaces {
+    fn code(&self) -> &'static str { "no-trailing-spaces" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD009"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut found = Vec::new();
+
+        for (offset, line) in ctx.lines() {
+            let trimmed = line.trim_end_matches([' ', '\t']);
+            let count = line.len() - trimmed.len();
+            if count == 0 { continue; }
+
+            let is_break = self.br_spaces >= 2 && count == self.br_spaces &&
+                !trimmed.trim_start().is_empty() && line[trimmed.len()..].bytes().all(|b| b == b' ');
+            if is_break { continue; }
+
+            found.push(SourcePos::new(offset + trimmed.len(), offset + line.len()));
+        }
+
+        for srcmap in found {
+            ctx.report("trailing whitespace", Some(srcmap));
+        }
+    }
+}
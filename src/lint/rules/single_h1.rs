@@ -0,0 +1,29 @@
+//! Document should have only one top-level heading (MD025).
+use crate::lint::{LintContext, LintRule};
+
+use super::heading_level;
+
+#[derive(Debug)]
+pub struct SingleH1;
+
+impl LintRule for SingleH1 {
+    fn code(&self) -> &'static str { "single-h1" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD025", "single-title"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut found = Vec::new();
+        let mut seen = false;
+
+        ctx.root.walk(|node, _| {
+            if heading_level(node) != Some(1) { return; }
+            if seen {
+                found.push(node.srcmap);
+            }
+            seen = true;
+        });
+
+        for srcmap in found {
+            ctx.report("multiple top-level headings in the same document", srcmap);
+        }
+    }
+}
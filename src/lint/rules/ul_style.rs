@@ -0,0 +1,32 @@
+//! Bullet lists should use the same marker (MD004).
+//!
+//! By default, the first list in a document determines the marker for all others,
+//! set [UlStyle::marker] to require a specific one.
+use crate::lint::{LintContext, LintRule};
+use crate::plugins::cmark::block::list::BulletList;
+
+#[derive(Debug, Default)]
+pub struct UlStyle {
+    /// Required marker (`-`, `*` or `+`), or `None` to be consistent with the first list.
+    pub marker: Option<char>,
+}
+
+impl LintRule for UlStyle {
+    fn code(&self) -> &'static str { "ul-style" }
+    fn aliases(&self) -> &'static [&'static str] { &["MD004"] }
+
+    fn check(&self, ctx: &mut LintContext) {
+        let mut expected = self.marker;
+        let mut found = Vec::new();
+
+        ctx.root.walk(|node, _| {
+            let Some(list) = node.cast::<BulletList>() else { return; };
+            let expected = *expected.get_or_insert(list.marker);
+            if list.marker != expected {
+                found.push((format!("expected `{}` list marker, found `{}`", expected, list.marker), node.srcmap));
+            }
+        });
+
+        found.into_iter().for_each(|(message, srcmap)| ctx.report(message, srcmap));
+    }
+}
@@ -8,22 +8,22 @@ pub use rule::*;
 #[doc(hidden)]
 pub mod builtin;
 
+use std::sync::Arc;
+
 use crate::common::ruler::Ruler;
-use crate::common::TypeKey;
+use crate::parser::core::RuleId;
+use crate::parser::diagnostics::{Diagnostic, Diagnostics};
 use crate::parser::extset::RootExtSet;
 use crate::parser::inline::InlineRoot;
 use crate::parser::node::NodeEmpty;
 use crate::{MarkdownIt, Node};
 
-type RuleFns = (
-    fn (&mut BlockState) -> Option<()>,
-    fn (&mut BlockState) -> Option<(Node, usize)>,
-);
+type RuleFns = Arc<dyn BlockRule>;
 
 #[derive(Debug, Default)]
 /// Block-level tokenizer.
 pub struct BlockParser {
-    ruler: Ruler<TypeKey, RuleFns>,
+    ruler: Ruler<RuleId, RuleFns>,
 }
 
 impl BlockParser {
@@ -48,6 +48,13 @@ impl BlockParser {
                 // If nesting level exceeded - skip tail to the end. That's not ordinary
                 // situation and we should not care about content.
                 if state.level >= state.md.max_nesting {
+                    let srcmap = state.get_map(state.line, state.line_max - 1);
+                    let diagnostic = Diagnostic::warning(
+                        "nesting-limit",
+                        format!("maximum nesting level ({}) exceeded, content is skipped", state.md.max_nesting),
+                        srcmap,
+                    );
+                    state.root_ext.get_or_insert_default::<Diagnostics>().push(diagnostic);
                     state.line = state.line_max;
                     break;
                 }
@@ -61,7 +68,7 @@ impl BlockParser {
                 let mut ok = None;
 
                 for rule in self.ruler.iter() {
-                    ok = rule.1(state);
+                    ok = rule.run(state);
                     if ok.is_some() {
                         break;
                     }
@@ -112,16 +119,38 @@ impl BlockParser {
         state.node
     }
 
-    pub fn add_rule<T: BlockRule>(&mut self) -> RuleBuilder<RuleFns> {
-        let item = self.ruler.add(TypeKey::of::<T>(), (T::check, T::run));
+    /// Add a block rule instance identified by its type.
+    pub fn add_rule<T: BlockRule>(&mut self, rule: T) -> RuleBuilder<'_, RuleFns> {
+        let item = self.ruler.add(RuleId::of::<T>(), Arc::new(rule));
+        RuleBuilder::new(item)
+    }
+
+    /// Add a block rule instance identified by explicit `id` (and also by its type).
+    pub fn add_rule_with_id<T: BlockRule>(&mut self, id: impl Into<RuleId>, rule: T) -> RuleBuilder<'_, RuleFns> {
+        let item = self.ruler.add(id.into(), Arc::new(rule));
+        item.alias(RuleId::of::<T>());
         RuleBuilder::new(item)
     }
 
     pub fn has_rule<T: BlockRule>(&mut self) -> bool {
-        self.ruler.contains(TypeKey::of::<T>())
+        self.ruler.contains(RuleId::of::<T>())
+    }
+
+    pub fn has_rule_id(&mut self, id: impl Into<RuleId>) -> bool {
+        self.ruler.contains(id.into())
+    }
+
+    /// Get mutable access to an already added rule instance
+    /// (returns `None` if rule isn't found or has a different type).
+    pub fn get_rule_mut<T: BlockRule>(&mut self, id: impl Into<RuleId>) -> Option<&mut T> {
+        Arc::get_mut(self.ruler.get_mut(id.into())?)?.downcast_mut::<T>()
     }
 
     pub fn remove_rule<T: BlockRule>(&mut self) {
-        self.ruler.remove(TypeKey::of::<T>());
+        self.ruler.remove(RuleId::of::<T>());
+    }
+
+    pub fn remove_rule_id(&mut self, id: impl Into<RuleId>) {
+        self.ruler.remove(id.into());
     }
 }
@@ -1,13 +1,47 @@
+use downcast_rs::{impl_downcast, Downcast};
+
 use crate::parser::core::rule_builder;
 use crate::Node;
 
 /// Each member of block rule chain must implement this trait
-pub trait BlockRule : 'static {
-    fn check(state: &mut super::BlockState) -> Option<()> {
-        Self::run(state).map(|_| ())
+pub trait BlockRule : Downcast + Send + Sync {
+    /// Check if this rule matches at the current line without creating any nodes,
+    /// used to find out whether a block structure should be terminated.
+    fn check(&self, state: &mut super::BlockState) -> Option<()> {
+        self.run(state).map(|_| ())
     }
 
-    fn run(state: &mut super::BlockState) -> Option<(Node, usize)>;
+    fn run(&self, state: &mut super::BlockState) -> Option<(Node, usize)>;
+
+    /// Whether this rule may interrupt `target` structure, i.e. start right after it
+    /// without an empty line in between. [check](BlockRule::check) is only
+    /// called for rules that return `true` here.
+    fn can_interrupt(&self, target: InterruptTarget) -> bool {
+        let _ = target;
+        true
+    }
 }
 
+impl_downcast!(BlockRule);
+
 rule_builder!(BlockRule);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Block structure that is checked for termination by
+/// [BlockState::test_rules_at_line](super::BlockState::test_rules_at_line).
+///
+/// This is an equivalent of `alt` option for block rules in markdown-it.js.
+pub enum InterruptTarget {
+    /// Paragraph or setext heading continuation line.
+    Paragraph,
+    /// Link reference definition continuation line.
+    Reference,
+    /// Lazy continuation line of a blockquote or any other [line-prefixed](crate::generics::block::line_prefix) block.
+    Blockquote,
+    /// Next item of a list.
+    List,
+    /// Next row of a table.
+    Table,
+    /// Any other block structure defined by a plugin.
+    Custom(&'static str),
+}
@@ -2,6 +2,7 @@
 //
 use crate::common::sourcemap::SourcePos;
 use crate::common::utils::calc_right_whitespace_with_tabstops;
+use crate::parser::block::InterruptTarget;
 use crate::parser::extset::RootExtSet;
 use crate::{MarkdownIt, Node};
 
@@ -170,9 +171,11 @@ impl<'a, 'b> BlockState<'a, 'b> {
     }
 
     #[must_use]
-    pub fn test_rules_at_line(&mut self) -> bool {
+    /// Check whether current line terminates `target` structure, i.e. if there's
+    /// any rule that is allowed to interrupt it and that matches on this line.
+    pub fn test_rules_at_line(&mut self, target: InterruptTarget) -> bool {
         for rule in self.md.block.ruler.iter() {
-            if rule.0(self).is_some() {
+            if rule.can_interrupt(target) && rule.check(self).is_some() {
                 return true;
             }
         }
@@ -15,6 +15,10 @@ impl Root {
 }
 
 impl NodeValue for Root {
+    fn clone_value(&self) -> Option<Box<dyn NodeValue>> {
+        Some(Box::new(Self { content: self.content.clone(), ext: self.ext.partial_clone() }))
+    }
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.contents(&node.children);
     }
@@ -1,29 +1,95 @@
+use downcast_rs::{impl_downcast, Downcast};
+use std::borrow::Cow;
+use std::fmt::{self, Debug};
+
+use crate::common::TypeKey;
 use crate::{MarkdownIt, Node};
 
 /// Each member of core rule chain must implement this trait
-pub trait CoreRule : 'static {
-    fn run(root: &mut Node, md: &MarkdownIt);
+pub trait CoreRule : Downcast + Send + Sync {
+    fn run(&self, root: &mut Node, md: &MarkdownIt);
+}
+
+impl_downcast!(CoreRule);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+/// Identifier of a rule inside of a rule chain.
+///
+/// Every rule is identified by its type, so you can refer to it as
+/// `RuleId::of::<T>()`. When you register multiple differently configured
+/// instances of the same rule type, give each of them an explicit name
+/// (e.g. `RuleId::from("emph_pair:*")`) to be able to refer to them separately.
+pub enum RuleId {
+    /// Rule identified by type of its implementation.
+    Type(TypeKey),
+    /// Rule identified by an arbitrary name.
+    Name(Cow<'static, str>),
+}
+
+impl RuleId {
+    #[must_use]
+    /// Identifier shared by all rules with implementation of type `T`.
+    pub fn of<T: ?Sized + 'static>() -> Self {
+        Self::Type(TypeKey::of::<T>())
+    }
+}
+
+impl From<TypeKey> for RuleId {
+    fn from(key: TypeKey) -> Self {
+        Self::Type(key)
+    }
+}
+
+impl From<&'static str> for RuleId {
+    fn from(name: &'static str) -> Self {
+        Self::Name(Cow::Borrowed(name))
+    }
+}
+
+impl From<String> for RuleId {
+    fn from(name: String) -> Self {
+        Self::Name(Cow::Owned(name))
+    }
+}
+
+impl Debug for RuleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Type(key) => write!(f, "{:?}", key),
+            Self::Name(name) => write!(f, "{:?}", name),
+        }
+    }
 }
 
 macro_rules! rule_builder {
     ($var: ident) => {
         /// Adjust positioning of a newly added rule in the chain.
         pub struct RuleBuilder<'a, T> {
-            item: &'a mut crate::common::ruler::RuleItem<crate::common::TypeKey, T>
+            item: &'a mut crate::common::ruler::RuleItem<crate::parser::core::RuleId, T>
         }
 
         impl<'a, T> RuleBuilder<'a, T> {
-            pub(crate) fn new(item: &'a mut crate::common::ruler::RuleItem<crate::common::TypeKey, T>) -> Self {
+            pub(crate) fn new(item: &'a mut crate::common::ruler::RuleItem<crate::parser::core::RuleId, T>) -> Self {
                 Self { item }
             }
 
             pub fn before<U: $var>(self) -> Self {
-                self.item.before(crate::common::TypeKey::of::<U>());
-                self
+                self.before_id(crate::parser::core::RuleId::of::<U>())
             }
 
             pub fn after<U: $var>(self) -> Self {
-                self.item.after(crate::common::TypeKey::of::<U>());
+                self.after_id(crate::parser::core::RuleId::of::<U>())
+            }
+
+            /// Same as [before](Self::before), but refers to a rule by its id.
+            pub fn before_id(self, id: impl Into<crate::parser::core::RuleId>) -> Self {
+                self.item.before(id.into());
+                self
+            }
+
+            /// Same as [after](Self::after), but refers to a rule by its id.
+            pub fn after_id(self, id: impl Into<crate::parser::core::RuleId>) -> Self {
+                self.item.after(id.into());
                 self
             }
 
@@ -38,12 +104,22 @@ macro_rules! rule_builder {
             }
 
             pub fn alias<U: $var>(self) -> Self {
-                self.item.alias(crate::common::TypeKey::of::<U>());
+                self.alias_id(crate::parser::core::RuleId::of::<U>())
+            }
+
+            /// Same as [alias](Self::alias), but uses an arbitrary id.
+            pub fn alias_id(self, id: impl Into<crate::parser::core::RuleId>) -> Self {
+                self.item.alias(id.into());
                 self
             }
 
             pub fn require<U: $var>(self) -> Self {
-                self.item.require(crate::common::TypeKey::of::<U>());
+                self.require_id(crate::parser::core::RuleId::of::<U>())
+            }
+
+            /// Same as [require](Self::require), but refers to a rule by its id.
+            pub fn require_id(self, id: impl Into<crate::parser::core::RuleId>) -> Self {
+                self.item.require(id.into());
                 self
             }
         }
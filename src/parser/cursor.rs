@@ -0,0 +1,257 @@
+//! Cursor for navigating and editing syntax tree in place.
+//!
+//! [walk_mut](Node::walk_mut) gives you one node at a time without access to
+//! its parent or siblings. [Cursor] points to a node in a tree and can move to
+//! its parent, children and siblings, insert and remove siblings, wrap them into
+//! a new node or move children of a node into its parent.
+//!
+//! ```rust
+//! use markdown_it::parser::inline::Text;
+//! use markdown_it::plugins::cmark::inline::emphasis::Strong;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! let mut ast = md.parse("make *it* **loud**");
+//!
+//! // replace all strong emphasis with its text in upper case
+//! let mut cursor = ast.cursor();
+//! while cursor.goto_next() {
+//!     if cursor.node().is::<Strong>() {
+//!         let content = cursor.node().collect_text().to_uppercase();
+//!         cursor.replace(markdown_it::Node::new(Text { content }));
+//!     }
+//! }
+//!
+//! assert_eq!(ast.render(), "<p>make <em>it</em> LOUD</p>\n");
+//! ```
+use crate::common::sourcemap::SourcePos;
+use crate::parser::inline::Text;
+use crate::Node;
+
+/// Position in a syntax tree, created by [Node::cursor].
+///
+/// Cursor never leaves the subtree it was created for, and always points
+/// to an existing node (initially the root of that subtree).
+#[derive(Debug)]
+pub struct Cursor<'a> {
+    root: &'a mut Node,
+    // indexes of children on the way from root to the current node
+    path: Vec<usize>,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(root: &'a mut Node) -> Self {
+        Self { root, path: Vec::new() }
+    }
+
+    /// Current node.
+    pub fn node(&self) -> &Node {
+        Self::resolve(self.root, &self.path)
+    }
+
+    /// Current node, mutable.
+    pub fn node_mut(&mut self) -> &mut Node {
+        Self::resolve_mut(self.root, &self.path)
+    }
+
+    /// Parent of the current node (`None` at the root).
+    pub fn parent(&self) -> Option<&Node> {
+        let (_, path) = self.path.split_last()?;
+        Some(Self::resolve(self.root, path))
+    }
+
+    /// Number of steps from the root to the current node.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Index of the current node among its siblings (`None` at the root).
+    pub fn index(&self) -> Option<usize> {
+        self.path.last().copied()
+    }
+
+    /// Move to the parent, returns `false` at the root.
+    pub fn goto_parent(&mut self) -> bool {
+        self.path.pop().is_some()
+    }
+
+    /// Move to the first child, returns `false` if there are no children.
+    pub fn goto_first_child(&mut self) -> bool {
+        if self.node().children.is_empty() { return false; }
+        self.path.push(0);
+        true
+    }
+
+    /// Move to the last child, returns `false` if there are no children.
+    pub fn goto_last_child(&mut self) -> bool {
+        let len = self.node().children.len();
+        if len == 0 { return false; }
+        self.path.push(len - 1);
+        true
+    }
+
+    /// Move to the next sibling, returns `false` if this is the last one.
+    pub fn goto_next_sibling(&mut self) -> bool {
+        let Some(parent) = self.parent() else { return false; };
+        let len = parent.children.len();
+        let idx = self.path.last_mut().unwrap();
+        if *idx + 1 >= len { return false; }
+        *idx += 1;
+        true
+    }
+
+    /// Move to the previous sibling, returns `false` if this is the first one.
+    pub fn goto_prev_sibling(&mut self) -> bool {
+        match self.path.last_mut() {
+            Some(idx) if *idx > 0 => { *idx -= 1; true }
+            _ => false,
+        }
+    }
+
+    /// Move to the next node in preorder (same order as [Node::walk]),
+    /// returns `false` if current node is the last one.
+    pub fn goto_next(&mut self) -> bool {
+        if self.goto_first_child() { return true; }
+
+        let path = self.path.clone();
+        while !self.goto_next_sibling() {
+            if !self.goto_parent() {
+                // nothing left, stay where we were
+                self.path = path;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Move to the previous node in preorder, returns `false` at the root.
+    pub fn goto_prev(&mut self) -> bool {
+        if self.goto_prev_sibling() {
+            while self.goto_last_child() {}
+            return true;
+        }
+        self.goto_parent()
+    }
+
+    /// Insert `node` before the current one, returns `false` at the root.
+    pub fn insert_before(&mut self, node: Node) -> bool {
+        let Some(idx) = self.index() else { return false; };
+        self.parent_mut().unwrap().children.insert(idx, node);
+        *self.path.last_mut().unwrap() += 1;
+        true
+    }
+
+    /// Insert `node` after the current one, returns `false` at the root.
+    pub fn insert_after(&mut self, node: Node) -> bool {
+        let Some(idx) = self.index() else { return false; };
+        self.parent_mut().unwrap().children.insert(idx + 1, node);
+        true
+    }
+
+    /// Replace current node (along with its children) with `node`, returns the old one.
+    pub fn replace(&mut self, node: Node) -> Node {
+        std::mem::replace(self.node_mut(), node)
+    }
+
+    /// Remove current node (along with its children) and return it, `None` at the root.
+    ///
+    /// Cursor moves to the previous node in preorder, so [goto_next](Cursor::goto_next)
+    /// continues with the node that followed removed one.
+    pub fn remove(&mut self) -> Option<Node> {
+        let idx = self.index()?;
+        let node = self.parent_mut().unwrap().children.remove(idx);
+        self.goto_prev();
+        Some(node)
+    }
+
+    /// Move children of the current node into its parent in place of it,
+    /// return the (now empty) node, `None` at the root.
+    ///
+    /// Cursor moves to the previous node in preorder, so [goto_next](Cursor::goto_next)
+    /// continues with the first of moved children.
+    pub fn unwrap(&mut self) -> Option<Node> {
+        let idx = self.index()?;
+        let mut node = std::mem::take(self.node_mut());
+        let children = std::mem::take(&mut node.children);
+        self.parent_mut().unwrap().children.splice(idx..=idx, children);
+        self.goto_prev();
+        Some(node)
+    }
+
+    /// Put current node and up to `count - 1` following siblings inside of `wrapper`
+    /// (after its existing children), cursor moves to `wrapper`.
+    ///
+    /// If `wrapper` has no source mapping, it covers all wrapped nodes.
+    /// Returns `false` at the root or if `count` is zero.
+    pub fn wrap(&mut self, count: usize, mut wrapper: Node) -> bool {
+        let Some(idx) = self.index() else { return false; };
+        if count == 0 { return false; }
+
+        let siblings = &mut self.parent_mut().unwrap().children;
+        let end = (idx + count).min(siblings.len());
+        let wrapped: Vec<_> = siblings.drain(idx..end).collect();
+
+        if wrapper.srcmap.is_none() {
+            wrapper.srcmap = wrapped.iter()
+                .filter_map(|node| node.srcmap.map(|map| map.get_byte_offsets()))
+                .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+                .map(|(start, end)| SourcePos::new(start, end));
+        }
+
+        wrapper.children.extend(wrapped);
+        siblings.insert(idx, wrapper);
+        true
+    }
+
+    /// Split current [Text] node at byte `offset` into two sibling nodes, cursor
+    /// stays at the first one.
+    ///
+    /// Source mapping is split at the same offset if it matches text length,
+    /// otherwise (e.g. text contains entities) both nodes keep the original one.
+    /// Returns `false` if current node isn't text, or offset is not inside of it.
+    pub fn split_text(&mut self, offset: usize) -> bool {
+        if self.parent().is_none() { return false; }
+
+        let node = self.node_mut();
+        let srcmap = node.srcmap;
+        let Some(text) = node.cast_mut::<Text>() else { return false; };
+        if offset == 0 || offset >= text.content.len() || !text.content.is_char_boundary(offset) {
+            return false;
+        }
+
+        let len = text.content.len();
+        let content = text.content.split_off(offset);
+        let mut second = Node::new(Text { content });
+        second.srcmap = srcmap;
+
+        if let Some(map) = srcmap {
+            let (start, end) = map.get_byte_offsets();
+            if end - start == len {
+                node.srcmap = Some(SourcePos::new(start, start + offset));
+                second.srcmap = Some(SourcePos::new(start + offset, end));
+            }
+        }
+
+        self.insert_after(second);
+        true
+    }
+
+    fn parent_mut(&mut self) -> Option<&mut Node> {
+        let (_, path) = self.path.split_last()?;
+        Some(Self::resolve_mut(self.root, path))
+    }
+
+    fn resolve<'b>(mut node: &'b Node, path: &[usize]) -> &'b Node {
+        for &idx in path {
+            node = &node.children[idx];
+        }
+        node
+    }
+
+    fn resolve_mut<'b>(mut node: &'b mut Node, path: &[usize]) -> &'b mut Node {
+        for &idx in path {
+            node = &mut node.children[idx];
+        }
+        node
+    }
+}
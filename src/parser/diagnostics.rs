@@ -0,0 +1,185 @@
+//! Warnings about questionable input, reported by rules during parsing.
+//!
+//! Markdown has no syntax errors, anything is a valid document. But some
+//! input (e.g. a code fence that is never closed) is likely a mistake,
+//! so rules report it to [Diagnostics] stored in root extension set.
+//!
+//! ```rust
+//! use markdown_it::parser::core::Root;
+//! use markdown_it::parser::diagnostics::Diagnostics;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let source = "```rust\nfn main() {}";
+//! let ast = md.parse(source);
+//! let diagnostics = ast.cast::<Root>().unwrap().ext.get::<Diagnostics>().unwrap();
+//! assert_eq!(diagnostics.format(source, "main.md"), "\
+//! warning[unclosed-fence]: code fence is not closed
+//!  --> main.md:1:1
+//!   |
+//! 1 | ```rust
+//!   | ^^^^^^^
+//!
+//! ");
+//! ```
+//!
+//! Use `state.root_ext.get_or_insert_default::<Diagnostics>().push(...)`
+//! to report diagnostics from your own rules.
+//!
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Write};
+
+use crate::common::sourcemap::{SourcePos, SourceWithLineStarts};
+use crate::parser::extset::RootExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How important the diagnostic is.
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info    => "info",
+            Self::Warning => "warning",
+            Self::Error   => "error",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Single message about a location in the source.
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Short machine-readable identifier, e.g. `unclosed-fence`.
+    pub code: Cow<'static, str>,
+    /// Human-readable description.
+    pub message: String,
+    /// Location in the source this message refers to.
+    pub srcmap: Option<SourcePos>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        code: impl Into<Cow<'static, str>>,
+        message: impl Into<String>,
+        srcmap: Option<SourcePos>,
+    ) -> Self {
+        Self { severity, code: code.into(), message: message.into(), srcmap }
+    }
+
+    pub fn warning(code: impl Into<Cow<'static, str>>, message: impl Into<String>, srcmap: Option<SourcePos>) -> Self {
+        Self::new(Severity::Warning, code, message, srcmap)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+/// List of diagnostics reported while parsing a document.
+pub struct Diagnostics {
+    list: Vec<Diagnostic>,
+    seen: HashSet<(Cow<'static, str>, Option<SourcePos>)>,
+}
+
+impl RootExt for Diagnostics {
+    fn clone_ext(&self) -> Option<Box<dyn RootExt>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a diagnostic, unless one with the same code and location is already added
+    /// (rules can be executed more than once on the same input, e.g. to check if
+    /// a list can interrupt a paragraph).
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        if self.seen.insert((diagnostic.code.clone(), diagnostic.srcmap)) {
+            self.list.push(diagnostic);
+        }
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Diagnostic> {
+        self.list.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Sort diagnostics by their position in the source.
+    pub fn sort(&mut self) {
+        self.list.sort_by_key(|d| d.srcmap.map(|pos| pos.get_byte_offsets()));
+    }
+
+    /// Format all diagnostics compiler-style, with a snippet of the `source` they refer to.
+    pub fn format(&self, source: &str, filename: &str) -> String {
+        let mapping = SourceWithLineStarts::new(source);
+        let mut result = String::new();
+        for diagnostic in self.list.iter() {
+            write_diagnostic(&mut result, diagnostic, source, &mapping, filename).unwrap();
+        }
+        result
+    }
+}
+
+impl<'a> IntoIterator for &'a Diagnostics {
+    type Item = &'a Diagnostic;
+    type IntoIter = std::slice::Iter<'a, Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+fn write_diagnostic(
+    out: &mut String,
+    diagnostic: &Diagnostic,
+    source: &str,
+    mapping: &SourceWithLineStarts,
+    filename: &str,
+) -> fmt::Result {
+    writeln!(out, "{}", diagnostic)?;
+
+    let Some(srcmap) = diagnostic.srcmap else {
+        writeln!(out, " --> {}", filename)?;
+        return writeln!(out);
+    };
+
+    let ((line, column), _) = srcmap.get_positions(mapping);
+    writeln!(out, " --> {}:{}:{}", filename, line, column)?;
+
+    // show first line of the range, with markers under it
+    let (start, end) = srcmap.get_byte_offsets();
+    let start = start.min(source.len());
+    let line_start = source[..start].rfind('\n').map(|x| x + 1).unwrap_or(0);
+    let line_end = source[start..].find(['\n', '\r']).map(|x| x + start).unwrap_or(source.len());
+    let end = end.clamp(start, line_end);
+
+    let line_no = line.to_string();
+    let gutter = " ".repeat(line_no.len());
+    let padding = " ".repeat(source[line_start..start].chars().count());
+    let markers = "^".repeat(source[start..end].chars().count().max(1));
+
+    writeln!(out, "{} |", gutter)?;
+    writeln!(out, "{} | {}", line_no, &source[line_start..line_end])?;
+    writeln!(out, "{} | {}{}", gutter, padding, markers)?;
+    writeln!(out)
+}
@@ -0,0 +1,648 @@
+//! Structural comparison of two syntax trees.
+//!
+//! Nodes are compared by html they render, not counting html of their children
+//! (which are compared separately). So only changes visible in the output are
+//! reported, while formatting differences are ignored: `*a*` equals `_a_`,
+//! setext heading equals ATX heading, reference link equals inline link with the
+//! same url, and adjacent text nodes are joined with whitespace collapsed (so
+//! re-wrapped paragraphs are equal too).
+//!
+//! ```rust
+//! use markdown_it::parser::diff::EditKind;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let old = md.parse("Title\n=====\n\n- *foo*\n  bar");
+//! let new = md.parse("# Title\n\n* _foo_ bar");
+//! assert!(old.structural_eq(&new));
+//!
+//! let new = md.parse("# Title\n\n* _foo_ baz");
+//! let edits = old.diff(&new);
+//! assert_eq!(edits.len(), 1);
+//! assert_eq!(edits[0].kind, EditKind::Update);
+//! assert_eq!(edits[0].old_srcmap.unwrap().get_byte_offsets(), (20, 26)); // "\n  bar"
+//! assert_eq!(edits[0].new_srcmap.unwrap().get_byte_offsets(), (16, 20)); // " baz"
+//! ```
+//!
+//! [render_diff] uses the same comparison to show changes in rendered html.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::common::sourcemap::SourcePos;
+use crate::common::utils::escape_html;
+use crate::parser::extset::RenderExtSet;
+use crate::parser::renderer::HTMLRenderer;
+use crate::{MarkdownIt, Node, Renderer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Type of a change.
+pub enum EditKind {
+    /// Nodes only exist in the new tree.
+    Insert,
+    /// Nodes only exist in the old tree.
+    Delete,
+    /// Nodes exist in both trees, but render differently
+    /// (changes in their children are reported separately).
+    Update,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Single change between two trees, returned by [Node::diff].
+pub struct Edit<'a> {
+    pub kind: EditKind,
+    /// Changed nodes in the old tree, empty for insertions (several
+    /// sibling nodes if they are text joined together).
+    pub old: &'a [Node],
+    /// Changed nodes in the new tree, empty for deletions.
+    pub new: &'a [Node],
+    /// Source position of the change in the old document (empty range
+    /// at the place of insertion for [EditKind::Insert]).
+    pub old_srcmap: Option<SourcePos>,
+    /// Source position of the change in the new document (empty range
+    /// at the place of deletion for [EditKind::Delete]).
+    pub new_srcmap: Option<SourcePos>,
+}
+
+/// Check that two trees render the same, ignoring formatting (see [module docs](self)).
+pub fn structural_eq(old: &Node, new: &Node) -> bool {
+    let old = Item::build(old);
+    let new = Item::build(new);
+    old.hash == new.hash && old.subtree_eq(&new)
+}
+
+/// Find all changes between two trees, in document order.
+pub fn diff<'a>(old: &'a Node, new: &'a Node) -> Vec<Edit<'a>> {
+    Differ::run(old, new).edits
+}
+
+/// Render `new` tree as html, marking changes made since `old` tree.
+///
+/// Changed words in text are wrapped in `<ins>` and `<del>`, as well as added
+/// or removed inline nodes. Added, removed and changed block nodes (paragraphs,
+/// list items, table rows, etc.) get `diff-ins`, `diff-del` and `diff-changed`
+/// classes respectively.
+///
+/// ```rust
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+///
+/// let html = markdown_it::parser::diff::render_diff(md, "- foo\n- bar", "- foo\n- baz\n- quux");
+/// assert_eq!(html, "<ul>\n<li>foo</li>\n<li><del>bar</del><ins>baz</ins></li>\n<li class=\"diff-ins\">quux</li>\n</ul>\n");
+/// ```
+pub fn render_diff(md: &MarkdownIt, old: &str, new: &str) -> String {
+    md.parse(old).render_diff(&md.parse(new))
+}
+
+/// Same as [render_diff], but for already parsed trees.
+pub fn render_tree_diff(old: &Node, new: &Node) -> String {
+    let plans = Differ::run(old, new).plans;
+    let mut fmt = DiffRenderer { inner: HTMLRenderer::new(), plans, stack: Vec::new(), mark: None };
+    fmt.render(new);
+    fmt.inner.into()
+}
+
+#[derive(Default)]
+/// Result of comparing two trees.
+struct Differ<'a> {
+    edits: Vec<Edit<'a>>,
+    // how to render children of changed nodes in the new tree
+    plans: HashMap<*const Node, Vec<Op<'a>>>,
+}
+
+/// Rendering step for children of a changed node.
+enum Op<'a> {
+    Keep(&'a [Node]),
+    Insert(&'a [Node], bool),
+    Delete(&'a [Node], bool),
+    Update { old: &'a [Node], new: &'a [Node], block: bool },
+    UpdateText { old: String, new: String },
+}
+
+impl<'a> Differ<'a> {
+    fn run(old: &'a Node, new: &'a Node) -> Self {
+        let old = Item::build(old);
+        let new = Item::build(new);
+        let mut differ = Self::default();
+
+        if old.signature != new.signature {
+            differ.edits.push(Edit {
+                kind: EditKind::Update,
+                old: old.nodes,
+                new: new.nodes,
+                old_srcmap: old.srcmap,
+                new_srcmap: new.srcmap,
+            });
+        }
+
+        differ.diff_children(&old, &new);
+        differ
+    }
+
+    fn diff_children(&mut self, old: &Item<'a>, new: &Item<'a>) {
+        // positions where deleted or inserted items would be in the other document
+        let mut old_point = old.start();
+        let mut new_point = new.start();
+        let point = |offset: Option<usize>| offset.map(|offset| SourcePos::new(offset, offset));
+        let mut ops = Vec::new();
+
+        let anchors = lcs(&old.children, &new.children, |a, b| a.subtree_eq(b));
+        let (mut old_idx, mut new_idx) = (0, 0);
+
+        for (old_end, new_end) in anchors.into_iter().chain([(old.children.len(), new.children.len())]) {
+            // items between unchanged ones are matched by node type
+            let old_gap = &old.children[old_idx..old_end];
+            let new_gap = &new.children[new_idx..new_end];
+            let pairs = lcs(old_gap, new_gap, |a, b| a.kind == b.kind);
+            let (mut i, mut j) = (0, 0);
+
+            for (pair_i, pair_j) in pairs.into_iter().chain([(old_gap.len(), new_gap.len())]) {
+                for item in &old_gap[i..pair_i] {
+                    self.edits.push(Edit {
+                        kind: EditKind::Delete,
+                        old: item.nodes,
+                        new: &[],
+                        old_srcmap: item.srcmap,
+                        new_srcmap: point(new_point),
+                    });
+                    ops.push(Op::Delete(item.nodes, item.block));
+                    old_point = item.end().or(old_point);
+                }
+
+                for item in &new_gap[j..pair_j] {
+                    self.edits.push(Edit {
+                        kind: EditKind::Insert,
+                        old: &[],
+                        new: item.nodes,
+                        old_srcmap: point(old_point),
+                        new_srcmap: item.srcmap,
+                    });
+                    ops.push(Op::Insert(item.nodes, item.block));
+                    new_point = item.end().or(new_point);
+                }
+
+                if pair_i < old_gap.len() {
+                    let (a, b) = (&old_gap[pair_i], &new_gap[pair_j]);
+                    if a.signature != b.signature {
+                        self.edits.push(Edit {
+                            kind: EditKind::Update,
+                            old: a.nodes,
+                            new: b.nodes,
+                            old_srcmap: a.srcmap,
+                            new_srcmap: b.srcmap,
+                        });
+                        ops.push(if a.kind.is_none() {
+                            Op::UpdateText { old: a.raw.clone(), new: b.raw.clone() }
+                        } else {
+                            Op::Update { old: a.nodes, new: b.nodes, block: b.block }
+                        });
+                    } else {
+                        ops.push(Op::Keep(b.nodes));
+                    }
+                    stacker::maybe_grow(64*1024, 1024*1024, || self.diff_children(a, b));
+                    old_point = a.end().or(old_point);
+                    new_point = b.end().or(new_point);
+                }
+
+                i = pair_i + 1;
+                j = pair_j + 1;
+            }
+
+            if old_end < old.children.len() {
+                ops.push(Op::Keep(new.children[new_end].nodes));
+                old_point = old.children[old_end].end().or(old_point);
+                new_point = new.children[new_end].end().or(new_point);
+            }
+            old_idx = old_end + 1;
+            new_idx = new_end + 1;
+        }
+
+        if new.kind.is_some() && ops.iter().any(|op| !matches!(op, Op::Keep(_))) {
+            self.plans.insert(&new.nodes[0] as *const Node, ops);
+        }
+    }
+}
+
+// placeholder for children in node signatures
+const CHILDREN: &str = "\u{1}";
+
+/// Node (or several text nodes) along with html it renders by itself.
+struct Item<'a> {
+    nodes: &'a [Node],
+    // node type name or `None` for text
+    kind: Option<&'static str>,
+    signature: String,
+    // html of text before collapsing whitespace
+    raw: String,
+    // whether it renders as a block (ends with a line break)
+    block: bool,
+    srcmap: Option<SourcePos>,
+    children: Vec<Item<'a>>,
+    hash: u64,
+}
+
+impl<'a> Item<'a> {
+    fn build(root: &'a Node) -> Self {
+        let mut fmt = SignatureRenderer::default();
+        fmt.render(root);
+        Self::node(root, &fmt.signatures)
+    }
+
+    fn node(node: &'a Node, signatures: &Signatures) -> Self {
+        let signature = &signatures[&(node as *const Node)];
+        let block = signature.ends_with('\n');
+        let signature = signature.trim().to_owned();
+        let children = Self::children(&node.children, signatures);
+        Self::new(std::slice::from_ref(node), Some(node.name()), signature, String::new(), block, children)
+    }
+
+    fn text(nodes: &'a [Node], signatures: &Signatures) -> Self {
+        let raw: String = nodes.iter().map(|node| signatures[&(node as *const Node)].as_str()).collect();
+        Self::new(nodes, None, collapse_whitespace(&raw), raw, false, Vec::new())
+    }
+
+    fn new(
+        nodes: &'a [Node],
+        kind: Option<&'static str>,
+        signature: String,
+        raw: String,
+        block: bool,
+        children: Vec<Self>,
+    ) -> Self {
+        let srcmap = nodes.iter()
+            .filter_map(|node| node.srcmap.map(|map| map.get_byte_offsets()))
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+            .map(|(start, end)| SourcePos::new(start, end));
+
+        let mut hasher = DefaultHasher::new();
+        signature.hash(&mut hasher);
+        for child in children.iter() {
+            child.hash.hash(&mut hasher);
+        }
+
+        Self { nodes, kind, signature, raw, block, srcmap, children, hash: hasher.finish() }
+    }
+
+    fn children(nodes: &'a [Node], signatures: &Signatures) -> Vec<Self> {
+        let mut result = Vec::new();
+        let mut idx = 0;
+
+        while idx < nodes.len() {
+            let node = &nodes[idx];
+            if signatures.get(&(node as *const Node)).is_none_or(|signature| signature.is_empty()) {
+                // node doesn't produce any output (or wasn't rendered by its parent),
+                // so it doesn't matter
+                idx += 1;
+            } else if is_text(node, signatures) {
+                let start = idx;
+                while idx < nodes.len() && is_text(&nodes[idx], signatures) { idx += 1; }
+                result.push(Self::text(&nodes[start..idx], signatures));
+            } else {
+                result.push(stacker::maybe_grow(64*1024, 1024*1024, || Self::node(node, signatures)));
+                idx += 1;
+            }
+        }
+
+        result
+    }
+
+    fn subtree_eq(&self, other: &Self) -> bool {
+        self.hash == other.hash &&
+            self.signature == other.signature &&
+            self.children.len() == other.children.len() &&
+            self.children.iter().zip(other.children.iter()).all(|(a, b)| a.subtree_eq(b))
+    }
+
+    fn start(&self) -> Option<usize> {
+        self.srcmap.map(|map| map.get_byte_offsets().0)
+    }
+
+    fn end(&self) -> Option<usize> {
+        self.srcmap.map(|map| map.get_byte_offsets().1)
+    }
+}
+
+type Signatures = HashMap<*const Node, String>;
+
+/// Leaf node rendering plain text without any tags.
+fn is_text(node: &Node, signatures: &Signatures) -> bool {
+    signatures.get(&(node as *const Node))
+        .is_some_and(|signature| !signature.contains('<') && !signature.contains(CHILDREN))
+}
+
+/// Replace each whitespace sequence with a single space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if !ch.is_whitespace() {
+            result.push(ch);
+        } else if !result.ends_with(' ') {
+            result.push(' ');
+        }
+    }
+    result
+}
+
+/// Close currently open `<ins>`/`<del>` wrapper and open a new one, unless it's the same.
+fn set_wrapper(fmt: &mut dyn Renderer, wrapper: &mut Option<&'static str>, tag: Option<&'static str>) {
+    if tag == *wrapper { return; }
+    if let Some(tag) = *wrapper { fmt.close(tag); }
+    if let Some(tag) = tag { fmt.open(tag, &[]); }
+    *wrapper = tag;
+}
+
+/// Mark changed words between two html strings without tags.
+///
+/// `wrapper` is the tag left open by previous nodes, and the last changed words
+/// are left open as well, so adjacent changes are merged.
+fn word_diff(fmt: &mut dyn Renderer, old: &str, new: &str, wrapper: &mut Option<&'static str>) {
+    fn split(text: &str) -> Vec<&str> {
+        let mut result = Vec::new();
+        let mut start = 0;
+        for (idx, ch) in text.char_indices().skip(1) {
+            let prev = text[..idx].chars().next_back().unwrap();
+            if ch.is_whitespace() != prev.is_whitespace() {
+                result.push(&text[start..idx]);
+                start = idx;
+            }
+        }
+        if start < text.len() { result.push(&text[start..]); }
+        result
+    }
+
+    let is_space = |word: &str| word.starts_with(char::is_whitespace);
+    let old = split(old);
+    let new = split(new);
+    let same = lcs(&old, &new, |a, b| a == b || is_space(a) && is_space(b));
+    let (mut i, mut j) = (0, 0);
+
+    for (same_i, same_j) in same.into_iter().chain([(old.len(), new.len())]) {
+        if i < same_i {
+            set_wrapper(fmt, wrapper, Some("del"));
+            old[i..same_i].iter().for_each(|word| fmt.text_raw(word));
+        }
+        if j < same_j {
+            set_wrapper(fmt, wrapper, Some("ins"));
+            new[j..same_j].iter().for_each(|word| fmt.text_raw(word));
+        }
+        if same_j < new.len() {
+            set_wrapper(fmt, wrapper, None);
+            fmt.text_raw(new[same_j]);
+        }
+        i = same_i + 1;
+        j = same_j + 1;
+    }
+}
+
+/// Longest common subsequence, returns pairs of matching indexes.
+///
+/// Common prefix and suffix are matched first, the rest is solved with
+/// Hirschberg's algorithm, which takes O(n*m) time but only linear memory.
+fn lcs<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> Vec<(usize, usize)> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| eq(x, y)).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| eq(x, y)).count();
+    let (a_end, b_end) = (a.len() - suffix, b.len() - suffix);
+
+    let mut result: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+    hirschberg(&a[prefix..a_end], &b[prefix..b_end], (prefix, prefix), &eq, &mut result);
+    result.extend((0..suffix).map(|i| (a_end + i, b_end + i)));
+    result
+}
+
+fn hirschberg<T>(a: &[T], b: &[T], offset: (usize, usize), eq: &impl Fn(&T, &T) -> bool, result: &mut Vec<(usize, usize)>) {
+    if a.is_empty() || b.is_empty() { return; }
+
+    if a.len() == 1 {
+        if let Some(j) = b.iter().position(|y| eq(&a[0], y)) {
+            result.push((offset.0, offset.1 + j));
+        }
+        return;
+    }
+
+    // split `a` in half, and find where to split `b` so that
+    // lcs(a_head, b_head) + lcs(a_tail, b_tail) is the largest
+    let mid = a.len() / 2;
+    let head = lcs_lengths(a[..mid].iter(), b.iter(), eq);
+    let tail = lcs_lengths(a[mid..].iter().rev(), b.iter().rev(), eq);
+    let split = (0..=b.len()).max_by_key(|&j| (head[j] + tail[b.len() - j], std::cmp::Reverse(j))).unwrap();
+
+    hirschberg(&a[..mid], &b[..split], offset, eq, result);
+    hirschberg(&a[mid..], &b[split..], (offset.0 + mid, offset.1 + split), eq, result);
+}
+
+/// Last row of lcs table, i.e. lengths of lcs of `a` and each prefix of `b`.
+fn lcs_lengths<'a, T: 'a>(
+    a: impl Iterator<Item = &'a T>,
+    b: impl Iterator<Item = &'a T> + Clone,
+    eq: &impl Fn(&T, &T) -> bool,
+) -> Vec<u32> {
+    let mut row = vec![0u32; b.clone().count() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (j, y) in b.clone().enumerate() {
+            let value = if eq(x, y) { diagonal + 1 } else { row[j + 1].max(row[j]) };
+            diagonal = row[j + 1];
+            row[j + 1] = value;
+        }
+    }
+    row
+}
+
+#[derive(Default)]
+/// Renderer that records html of each node separately, replacing children with a placeholder.
+struct SignatureRenderer {
+    stack: Vec<String>,
+    signatures: HashMap<*const Node, String>,
+    ext: RenderExtSet,
+}
+
+impl SignatureRenderer {
+    fn render(&mut self, node: &Node) {
+        self.stack.push(String::new());
+        node.node_value.render(node, self);
+        let signature = self.stack.pop().unwrap();
+        self.signatures.insert(node as *const Node, signature);
+    }
+
+    fn buf(&mut self) -> &mut String {
+        self.stack.last_mut().unwrap()
+    }
+
+    fn push_attrs(&mut self, attrs: &[(&str, String)]) {
+        for (name, value) in attrs {
+            let attr = format!(" {}=\"{}\"", escape_html(name), escape_html(value));
+            self.buf().push_str(&attr);
+        }
+    }
+}
+
+impl Renderer for SignatureRenderer {
+    fn open(&mut self, tag: &str, attrs: &[(&str, String)]) {
+        self.buf().push('<');
+        self.buf().push_str(tag);
+        self.push_attrs(attrs);
+        self.buf().push('>');
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.buf().push_str("</");
+        self.buf().push_str(tag);
+        self.buf().push('>');
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(&str, String)]) {
+        self.buf().push('<');
+        self.buf().push_str(tag);
+        self.push_attrs(attrs);
+        self.buf().push_str(" />");
+    }
+
+    fn contents(&mut self, nodes: &[Node]) {
+        // children are compared separately, only mark where they are
+        self.buf().push_str(CHILDREN);
+        for node in nodes.iter() {
+            self.render(node);
+        }
+    }
+
+    fn cr(&mut self) {
+        self.buf().push('\n');
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = escape_html(text);
+        self.buf().push_str(&text);
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        self.buf().push_str(text);
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        &mut self.ext
+    }
+}
+
+/// Renderer that follows plans made by [Differ] when rendering children of changed nodes.
+struct DiffRenderer<'a> {
+    inner: HTMLRenderer<false>,
+    plans: HashMap<*const Node, Vec<Op<'a>>>,
+    // nodes currently being rendered, along with their children
+    stack: Vec<(*const Node, *const Node)>,
+    // class to add to the first tag of a node being rendered
+    mark: Option<&'static str>,
+}
+
+impl DiffRenderer<'_> {
+    fn render(&mut self, node: &Node) {
+        self.stack.push((node, node.children.as_ptr()));
+        node.node_value.render(node, self);
+        self.stack.pop();
+    }
+
+    fn render_marked(&mut self, nodes: &[Node], class: &'static str) {
+        for node in nodes {
+            self.mark = Some(class);
+            self.render(node);
+            self.mark = None;
+        }
+    }
+
+    fn with_mark<'b>(&mut self, attrs: &[(&'b str, String)]) -> Vec<(&'b str, String)> {
+        let mut attrs = attrs.to_vec();
+        if let Some(class) = self.mark.take() {
+            match attrs.iter_mut().find(|(name, _)| *name == "class") {
+                Some((_, value)) => { value.push(' '); value.push_str(class); }
+                None => attrs.push(("class", class.to_owned())),
+            }
+        }
+        attrs
+    }
+}
+
+impl Renderer for DiffRenderer<'_> {
+    fn open(&mut self, tag: &str, attrs: &[(&str, String)]) {
+        if self.mark.is_none() { return self.inner.open(tag, attrs); }
+        let attrs = self.with_mark(attrs);
+        self.inner.open(tag, &attrs);
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.inner.close(tag);
+    }
+
+    fn self_close(&mut self, tag: &str, attrs: &[(&str, String)]) {
+        if self.mark.is_none() { return self.inner.self_close(tag, attrs); }
+        let attrs = self.with_mark(attrs);
+        self.inner.self_close(tag, &attrs);
+    }
+
+    fn contents(&mut self, nodes: &[Node]) {
+        // class only applies to the outer tag of a node
+        self.mark = None;
+
+        // only use the plan if the node renders its own children
+        let plan = self.stack.last().copied()
+            .filter(|&(_, children)| std::ptr::eq(children, nodes.as_ptr()))
+            .and_then(|(parent, _)| self.plans.remove(&parent));
+
+        let Some(plan) = plan else {
+            nodes.iter().for_each(|node| self.render(node));
+            return;
+        };
+
+        // adjacent inline insertions (and deletions) share one tag
+        let mut wrapper = None;
+
+        for op in plan.iter() {
+            let tag = match op {
+                Op::Insert(_, false) => Some("ins"),
+                Op::Delete(_, false) | Op::Update { block: false, .. } => Some("del"),
+                // changed words take care of the wrapper themselves
+                Op::UpdateText { .. } => wrapper,
+                _ => None,
+            };
+            set_wrapper(self, &mut wrapper, tag);
+
+            match op {
+                Op::Keep(nodes) | Op::Insert(nodes, false) | Op::Delete(nodes, false) => {
+                    nodes.iter().for_each(|node| self.render(node));
+                }
+                Op::Insert(nodes, true) => self.render_marked(nodes, "diff-ins"),
+                Op::Delete(nodes, true) => self.render_marked(nodes, "diff-del"),
+                Op::Update { new, block: true, .. } => self.render_marked(new, "diff-changed"),
+                Op::Update { old, new, block: false } => {
+                    // whole new node is shown as inserted, no need to mark changes inside
+                    for node in new.iter() {
+                        node.walk(|node, _| { self.plans.remove(&(node as *const Node)); });
+                    }
+                    old.iter().for_each(|node| self.render(node));
+                    self.inner.close("del");
+                    self.inner.open("ins", &[]);
+                    wrapper = Some("ins");
+                    new.iter().for_each(|node| self.render(node));
+                }
+                Op::UpdateText { old, new } => word_diff(self, old, new, &mut wrapper),
+            }
+        }
+
+        if let Some(tag) = wrapper { self.inner.close(tag); }
+    }
+
+    fn cr(&mut self) {
+        self.inner.cr();
+    }
+
+    fn text(&mut self, text: &str) {
+        self.inner.text(text);
+    }
+
+    fn text_raw(&mut self, text: &str) {
+        self.inner.text_raw(text);
+    }
+
+    fn ext(&mut self) -> &mut RenderExtSet {
+        self.inner.ext()
+    }
+}
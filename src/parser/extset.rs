@@ -11,20 +11,46 @@ impl_downcast!(MarkdownItExt);
 extension_set!(MarkdownItExtSet, MarkdownItExt);
 
 /// Extension set member for an arbitrary AST node.
-pub trait NodeExt : Debug + Downcast + Send + Sync {}
+pub trait NodeExt : Debug + Downcast + Send + Sync {
+    /// Copy this value when node is cloned (see [Node::try_clone](crate::Node::try_clone)),
+    /// `None` if it can't be copied, in which case it is skipped.
+    fn clone_ext(&self) -> Option<Box<dyn NodeExt>> {
+        None
+    }
+}
 impl_downcast!(NodeExt);
 extension_set!(NodeExtSet, NodeExt);
 
+impl NodeExtSet {
+    /// Copy all extensions that support cloning, skipping others.
+    pub fn partial_clone(&self) -> Self {
+        Self(self.0.iter().filter_map(|(key, value)| Some((*key, value.clone_ext()?))).collect())
+    }
+}
+
 /// Extension set member for an inline context.
 pub trait InlineRootExt : Debug + Downcast + Send + Sync {}
 impl_downcast!(InlineRootExt);
 extension_set!(InlineRootExtSet, InlineRootExt);
 
 /// Extension set member for a block context.
-pub trait RootExt : Debug + Downcast + Send + Sync {}
+pub trait RootExt : Debug + Downcast + Send + Sync {
+    /// Copy this value when root node is cloned (see [Node::try_clone](crate::Node::try_clone)),
+    /// `None` if it can't be copied, in which case it is skipped.
+    fn clone_ext(&self) -> Option<Box<dyn RootExt>> {
+        None
+    }
+}
 impl_downcast!(RootExt);
 extension_set!(RootExtSet, RootExt);
 
+impl RootExtSet {
+    /// Copy all extensions that support cloning, skipping others.
+    pub fn partial_clone(&self) -> Self {
+        Self(self.0.iter().filter_map(|(key, value)| Some((*key, value.clone_ext()?))).collect())
+    }
+}
+
 /// Extension set member for a renderer context.
 pub trait RenderExt : Debug + Downcast + Send + Sync {}
 impl_downcast!(RenderExt);
@@ -114,7 +140,6 @@ pub(crate) use extension_set;
 
 #[cfg(test)]
 mod tests {
-    use super::extension_set;
     use downcast_rs::{Downcast, impl_downcast};
     use std::fmt::Debug;
 
@@ -0,0 +1,233 @@
+//! Syntax highlighting of code blocks.
+//!
+//! Code blocks and fences don't highlight anything by themselves. Instead, they
+//! call a [Highlighter] set with [set_highlighter] when rendered, so you can use
+//! any library you like (the [syntect](crate::plugins::extra::syntect) plugin is
+//! one implementation). Syntax tree is not changed, code nodes stay available
+//! to other rules.
+//!
+//! ```rust
+//! use markdown_it::parser::highlighter::{self, Highlighter};
+//!
+//! #[derive(Debug)]
+//! struct Shout;
+//! impl Highlighter for Shout {
+//!     fn highlight(&self, lang: Option<&str>, code: &str) -> Option<String> {
+//!         (lang == Some("shout")).then(|| format!("<b>{}</b>", code.to_uppercase()))
+//!     }
+//! }
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! highlighter::set_highlighter(md, Shout);
+//!
+//! let html = md.parse("```shout\nhello\n```").render();
+//! assert_eq!(html, "<pre><code class=\"language-shout\"><b>HELLO\n</b></code></pre>\n");
+//! ```
+//!
+use downcast_rs::{impl_downcast, Downcast};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use crate::common::utils::escape_html;
+use crate::parser::extset::MarkdownItExt;
+use crate::{MarkdownIt, Renderer};
+
+/// Each highlighter must implement this trait.
+pub trait Highlighter : Debug + Downcast + Send + Sync {
+    /// Highlight `code` written in language `lang` (`None` for indented code
+    /// and fences without info string).
+    ///
+    /// Returns html to be placed inside of `<code>` element, or `None` if code
+    /// should be rendered as plain text. Returned html must be properly escaped.
+    fn highlight(&self, lang: Option<&str>, code: &str) -> Option<String>;
+
+    /// Attributes added to `<pre>` element of highlighted blocks
+    /// (e.g. background color or theme class).
+    fn pre_attrs(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+
+    /// Attributes added to `<code>` element of highlighted inline code spans
+    /// (they don't have a `<pre>` element).
+    fn inline_attrs(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+impl_downcast!(Highlighter);
+
+#[derive(Debug, Clone)]
+struct HighlighterSettings(Arc<dyn Highlighter>);
+impl MarkdownItExt for HighlighterSettings {}
+
+/// Use `highlighter` for all code blocks parsed after this call.
+pub fn set_highlighter(md: &mut MarkdownIt, highlighter: impl Highlighter) {
+    md.ext.insert(HighlighterSettings(Arc::new(highlighter)));
+}
+
+/// Turn off highlighting.
+pub fn remove_highlighter(md: &mut MarkdownIt) {
+    md.ext.remove::<HighlighterSettings>();
+}
+
+/// Currently used highlighter, if any.
+pub fn get_highlighter(md: &MarkdownIt) -> Option<Arc<dyn Highlighter>> {
+    md.ext.get::<HighlighterSettings>().map(|settings| settings.0.clone())
+}
+
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+/// Information about a code block used by [render_code_block].
+///
+/// Create it with `CodeBlockInfo::default()` and set the fields you need,
+/// more fields may be added in the future.
+pub struct CodeBlockInfo<'a> {
+    /// Language name (`None` for indented code and fences without info string).
+    pub lang: Option<&'a str>,
+    /// Caption shown above the code, wraps the block in a `<figure>` element.
+    pub title: Option<&'a str>,
+    /// Whether line numbers should be shown.
+    pub line_numbers: bool,
+    /// Ranges of lines (starting from 1) to highlight.
+    pub highlighted_lines: &'a [RangeInclusive<usize>],
+}
+
+impl CodeBlockInfo<'_> {
+    /// Check if line (starting from 1) should be highlighted.
+    pub fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted_lines.iter().any(|range| range.contains(&line))
+    }
+}
+
+/// Render a `<pre><code>` element, highlighting `code` if possible.
+pub fn render_code_block(
+    fmt: &mut dyn Renderer,
+    highlighter: Option<&dyn Highlighter>,
+    info: &CodeBlockInfo,
+    code: &str,
+    attrs: &[(&'static str, String)],
+) {
+    let lang = info.lang;
+    let highlighted = highlighter.and_then(|h| Some((h.highlight(lang, code)?, h.pre_attrs())));
+    let (html, pre_attrs) = match highlighted {
+        Some((html, pre_attrs)) => (Cow::Owned(html), pre_attrs),
+        None => (escape_html(code), Vec::new()),
+    };
+
+    let title = info.title;
+    let line_numbers = info.line_numbers;
+
+    fmt.cr();
+    if let Some(title) = title {
+        fmt.open("figure", &[("class", "code-block".into())]);
+        fmt.cr();
+        fmt.open("figcaption", &[]);
+        fmt.text(title);
+        fmt.close("figcaption");
+        fmt.cr();
+    }
+
+    fmt.open("pre", &pre_attrs);
+    fmt.open("code", attrs);
+
+    if line_numbers || !info.highlighted_lines.is_empty() {
+        for (idx, line) in split_lines(&html).into_iter().enumerate() {
+            let class = if info.is_highlighted(idx + 1) { "line highlighted" } else { "line" };
+            fmt.open("span", &[("class", class.into())]);
+            if line_numbers {
+                fmt.open("span", &[("class", "line-number".into())]);
+                fmt.text(&(idx + 1).to_string());
+                fmt.close("span");
+            }
+            fmt.text_raw(&line);
+            fmt.close("span");
+            fmt.text_raw("\n");
+        }
+    } else {
+        fmt.text_raw(&html);
+    }
+
+    fmt.close("code");
+    fmt.close("pre");
+    fmt.cr();
+
+    if title.is_some() {
+        fmt.close("figure");
+        fmt.cr();
+    }
+}
+
+/// Split html into lines, closing tags open at the end of each line
+/// and opening them again on the next one.
+fn split_lines(html: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut open_tags: Vec<&str> = Vec::new();
+    let mut line = String::new();
+    let mut has_text = false;
+    let mut rest = html;
+
+    while let Some(ch) = rest.chars().next() {
+        match ch {
+            '<' => {
+                let end = rest.find('>').map(|x| x + 1).unwrap_or(rest.len());
+                let tag = &rest[..end];
+                if tag.starts_with("</") {
+                    open_tags.pop();
+                } else if !tag.ends_with("/>") {
+                    open_tags.push(tag);
+                }
+                line.push_str(tag);
+                rest = &rest[end..];
+            }
+            '\n' => {
+                for tag in open_tags.iter().rev() {
+                    let name = tag[1..].split(|c: char| c.is_whitespace() || c == '>').next().unwrap();
+                    line.push_str("</");
+                    line.push_str(name);
+                    line.push('>');
+                }
+                lines.push(std::mem::take(&mut line));
+                open_tags.iter().for_each(|tag| line.push_str(tag));
+                has_text = false;
+                rest = &rest[1..];
+            }
+            _ => {
+                line.push(ch);
+                has_text = true;
+                rest = &rest[ch.len_utf8()..];
+            }
+        }
+    }
+
+    // last line is either unterminated or has only closing tags in it
+    if has_text {
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_lines;
+
+    #[test]
+    fn split_lines_reopens_tags() {
+        let html = "<span class=\"a\">foo\n<b>bar</b>\n\nbaz</span>\n";
+        assert_eq!(split_lines(html), [
+            "<span class=\"a\">foo</span>",
+            "<span class=\"a\"><b>bar</b></span>",
+            "<span class=\"a\"></span>",
+            "<span class=\"a\">baz</span>",
+        ]);
+    }
+
+    #[test]
+    fn split_lines_without_tags() {
+        assert_eq!(split_lines("foo\n\nbar"), ["foo", "", "bar"]);
+        assert!(split_lines("").is_empty());
+    }
+}
@@ -4,6 +4,7 @@ pub(super) mod inline_parser;
 pub(super) mod skip_text;
 
 pub use inline_parser::InlineParserRule;
+pub(crate) use inline_parser::parse_inline_roots;
 pub use skip_text::TextScanner;
 
 pub fn add(md: &mut MarkdownIt) {
@@ -3,22 +3,24 @@
 //!
 use regex::{self, Regex};
 
-use crate::parser::inline::{InlineRule, InlineState};
+use crate::parser::inline::{InlineRule, InlineState, Trigger};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Plain text AST node.
 pub struct Text {
     pub content: String
 }
 
 impl NodeValue for Text {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.text(&self.content);
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 /// Escaped text AST node (backslash escapes and entities).
 pub struct TextSpecial {
     pub content: String,
@@ -27,20 +29,28 @@ pub struct TextSpecial {
 }
 
 impl NodeValue for TextSpecial {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.text(&self.content);
     }
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.inline.add_rule::<TextScanner>()
+    md.inline.add_rule(TextScanner)
         .before_all();
 }
 
 #[derive(Debug)]
-pub(crate) enum TextScannerImpl {
-    SkipPunct,
-    SkipRegex(Regex),
+pub(crate) struct TextScannerImpl {
+    skip: TextSkipImpl,
+    word_start: Vec<fn (char) -> bool>,
+}
+
+#[derive(Debug)]
+enum TextSkipImpl {
+    Punct,
+    Regex(Regex),
 }
 
 /// Rule to skip pure text
@@ -54,7 +64,23 @@ pub(crate) enum TextScannerImpl {
 pub struct TextScanner;
 
 impl TextScanner {
-    fn choose_text_impl(charmap: Vec<char>) -> TextScannerImpl {
+    fn choose_text_impl(triggers: Vec<Trigger>) -> TextScannerImpl {
+        let mut charmap = triggers.iter().flat_map(|t| t.chars().iter().copied()).collect::<Vec<_>>();
+        charmap.sort_unstable();
+        charmap.dedup();
+
+        let word_start = triggers.iter().filter_map(|t| match t {
+            Trigger::WordStart(f) => Some(*f),
+            _ => None,
+        }).collect();
+
+        TextScannerImpl {
+            skip: Self::choose_skip_impl(charmap),
+            word_start,
+        }
+    }
+
+    fn choose_skip_impl(charmap: Vec<char>) -> TextSkipImpl {
         let mut can_use_punct = true;
         for ch in charmap.iter() {
             match ch {
@@ -69,9 +95,9 @@ impl TextScanner {
         }
 
         if can_use_punct {
-            TextScannerImpl::SkipPunct
+            TextSkipImpl::Punct
         } else {
-            TextScannerImpl::SkipRegex(
+            TextSkipImpl::Regex(
                 Regex::new(
                     // [] panics on "unclosed character class", but it cannot happen here
                     // (we'd use punct rule instead)
@@ -85,13 +111,13 @@ impl TextScanner {
 
     fn find_text_length(state: &mut InlineState) -> usize {
         let text_impl = state.md.inline.text_impl.get_or_init(
-            || Self::choose_text_impl(state.md.inline.text_charmap.keys().copied().collect())
+            || Self::choose_text_impl(state.md.inline.ruler.iter().map(|rule| rule.trigger()).collect())
         );
 
         let mut len = 0;
 
-        match text_impl {
-            TextScannerImpl::SkipPunct => {
+        match &text_impl.skip {
+            TextSkipImpl::Punct => {
                 let mut chars = state.src[state.pos..state.pos_max].chars();
 
                 loop {
@@ -110,27 +136,38 @@ impl TextScanner {
                     }
                 }
             }
-            TextScannerImpl::SkipRegex(re) => {
+            TextSkipImpl::Regex(re) => {
                 if let Some(capture) = re.find(&state.src[state.pos..state.pos_max]) {
                     len = capture.end();
                 }
             }
         }
 
+        if !text_impl.word_start.is_empty() && len > 0 {
+            // stop before the first character that can start a word for any of the rules
+            let mut prev = state.src[..state.pos].chars().next_back();
+            for (offset, ch) in state.src[state.pos..state.pos + len].char_indices() {
+                if text_impl.word_start.iter().any(|f| f(ch) && !prev.is_some_and(f)) {
+                    return offset;
+                }
+                prev = Some(ch);
+            }
+        }
+
         len
     }
 }
 
 impl InlineRule for TextScanner {
-    const MARKER: char = '\0';
+    fn trigger(&self) -> Trigger { Trigger::None }
 
-    fn check(state: &mut InlineState) -> Option<usize> {
+    fn check(&self, state: &mut InlineState) -> Option<usize> {
         let len = Self::find_text_length(state);
         if len == 0 { return None; }
         Some(len)
     }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let len = Self::find_text_length(state);
         if len == 0 { return None; }
         state.trailing_text_push(state.pos, state.pos + len);
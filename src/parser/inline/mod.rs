@@ -1,6 +1,6 @@
 //! Inline rule chain
 use once_cell::sync::OnceCell;
-use std::collections::HashMap;
+use std::sync::Arc;
 
 mod state;
 pub use state::*;
@@ -16,22 +16,19 @@ pub use builtin::skip_text::{Text, TextSpecial};
 use builtin::skip_text::TextScannerImpl;
 
 use crate::{MarkdownIt, Node};
-use crate::common::TypeKey;
 use crate::common::ruler::Ruler;
+use crate::parser::core::RuleId;
+use crate::parser::diagnostics::{Diagnostic, Diagnostics};
 use crate::parser::extset::{InlineRootExtSet, RootExtSet};
 
 use super::node::NodeEmpty;
 
-type RuleFns = (
-    fn (&mut InlineState) -> Option<usize>,
-    fn (&mut InlineState) -> Option<(Node, usize)>,
-);
+type RuleFns = Arc<dyn InlineRule>;
 
 #[derive(Debug, Default)]
 /// Inline-level tokenizer.
 pub struct InlineParser {
-    ruler: Ruler<TypeKey, RuleFns>,
-    text_charmap: HashMap<char, Vec<TypeKey>>,
+    ruler: Ruler<RuleId, RuleFns>,
     text_impl: OnceCell<TextScannerImpl>,
 }
 
@@ -49,7 +46,7 @@ impl InlineParser {
 
             if state.level < state.md.max_nesting {
                 for rule in self.ruler.iter() {
-                    ok = rule.0(state);
+                    ok = rule.check(state);
                     if ok.is_some() {
                         break;
                     }
@@ -84,6 +81,16 @@ impl InlineParser {
         stacker::maybe_grow(64*1024, 1024*1024, || {
             let end = state.pos_max;
 
+            if state.level >= state.md.max_nesting && state.pos < end {
+                let srcmap = state.get_map(state.pos, end);
+                let diagnostic = Diagnostic::warning(
+                    "nesting-limit",
+                    format!("maximum nesting level ({}) exceeded, content is kept as text", state.md.max_nesting),
+                    srcmap,
+                );
+                state.root_ext.get_or_insert_default::<Diagnostics>().push(diagnostic);
+            }
+
             while state.pos < end {
                 // Try all possible rules.
                 // On success, rule should:
@@ -95,7 +102,7 @@ impl InlineParser {
 
                 if state.level < state.md.max_nesting {
                     for rule in self.ruler.iter() {
-                        ok = rule.1(state);
+                        ok = rule.run(state);
                         if ok.is_some() {
                             break;
                         }
@@ -136,27 +143,42 @@ impl InlineParser {
         state.node
     }
 
-    pub fn add_rule<T: InlineRule>(&mut self) -> RuleBuilder<RuleFns> {
-        if T::MARKER != '\0' {
-            let charvec = self.text_charmap.entry(T::MARKER).or_default();
-            charvec.push(TypeKey::of::<T>());
-        }
+    /// Add an inline rule instance identified by its type.
+    pub fn add_rule<T: InlineRule>(&mut self, rule: T) -> RuleBuilder<'_, RuleFns> {
+        self.text_impl = OnceCell::new();
+        let item = self.ruler.add(RuleId::of::<T>(), Arc::new(rule));
+        RuleBuilder::new(item)
+    }
 
-        let item = self.ruler.add(TypeKey::of::<T>(), (T::check, T::run));
+    /// Add an inline rule instance identified by explicit `id` (and also by its type).
+    pub fn add_rule_with_id<T: InlineRule>(&mut self, id: impl Into<RuleId>, rule: T) -> RuleBuilder<'_, RuleFns> {
+        self.text_impl = OnceCell::new();
+        let item = self.ruler.add(id.into(), Arc::new(rule));
+        item.alias(RuleId::of::<T>());
         RuleBuilder::new(item)
     }
 
     pub fn has_rule<T: InlineRule>(&mut self) -> bool {
-        self.ruler.contains(TypeKey::of::<T>())
+        self.ruler.contains(RuleId::of::<T>())
+    }
+
+    pub fn has_rule_id(&mut self, id: impl Into<RuleId>) -> bool {
+        self.ruler.contains(id.into())
+    }
+
+    /// Get mutable access to an already added rule instance
+    /// (returns `None` if rule isn't found or has a different type).
+    pub fn get_rule_mut<T: InlineRule>(&mut self, id: impl Into<RuleId>) -> Option<&mut T> {
+        self.text_impl = OnceCell::new();
+        Arc::get_mut(self.ruler.get_mut(id.into())?)?.downcast_mut::<T>()
     }
 
     pub fn remove_rule<T: InlineRule>(&mut self) {
-        if T::MARKER != '\0' {
-            let mut charvec = self.text_charmap.remove(&T::MARKER).unwrap_or_default();
-            charvec.retain(|x| *x != TypeKey::of::<T>());
-            self.text_charmap.insert(T::MARKER, charvec);
-        }
+        self.remove_rule_id(RuleId::of::<T>());
+    }
 
-        self.ruler.remove(TypeKey::of::<T>());
+    pub fn remove_rule_id(&mut self, id: impl Into<RuleId>) {
+        self.text_impl = OnceCell::new();
+        self.ruler.remove(id.into());
     }
 }
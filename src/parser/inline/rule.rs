@@ -1,15 +1,49 @@
+use downcast_rs::{impl_downcast, Downcast};
+
 use crate::parser::core::rule_builder;
 use crate::Node;
 
 /// Each member of inline rule chain must implement this trait
-pub trait InlineRule : 'static {
-    const MARKER: char;
+pub trait InlineRule : Downcast + Send + Sync {
+    /// Positions where this rule should be tried (other characters may get skipped over).
+    fn trigger(&self) -> Trigger;
 
-    fn check(state: &mut super::InlineState) -> Option<usize> {
-        Self::run(state).map(|(_node, len)| len)
+    fn check(&self, state: &mut super::InlineState) -> Option<usize> {
+        self.run(state).map(|(_node, len)| len)
     }
 
-    fn run(state: &mut super::InlineState) -> Option<(Node, usize)>;
+    fn run(&self, state: &mut super::InlineState) -> Option<(Node, usize)>;
 }
 
+impl_downcast!(InlineRule);
+
 rule_builder!(InlineRule);
+
+#[derive(Debug, Clone)]
+/// Describes where in the text an inline rule can start, so that
+/// [TextScanner](super::builtin::TextScanner) knows which characters are
+/// safe to skip over as plain text.
+pub enum Trigger {
+    /// Rule doesn't have a trigger of its own, it only gets called
+    /// at positions where some other rule is triggered.
+    None,
+    /// Rule is triggered by a single character (e.g. `<` for autolinks).
+    Char(char),
+    /// Rule is triggered by any of these characters (e.g. `@` and `#` for mentions and hashtags).
+    Chars(Vec<char>),
+    /// Rule is triggered at the start of every word, i.e. on a character
+    /// that matches the predicate and is not preceded by one that matches it
+    /// (e.g. `char::is_alphanumeric` to look for URL-like words).
+    WordStart(fn (char) -> bool),
+}
+
+impl Trigger {
+    /// Characters that trigger this rule unconditionally.
+    pub fn chars(&self) -> &[char] {
+        match self {
+            Self::Char(ch) => std::slice::from_ref(ch),
+            Self::Chars(chars) => chars,
+            Self::None | Self::WordStart(_) => &[],
+        }
+    }
+}
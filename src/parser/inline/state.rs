@@ -232,6 +232,17 @@ impl<'a, 'b> InlineState<'a, 'b> {
         self.srcmap[line].1 + (pos - self.srcmap[line].0)
     }
 
+    /// Reverse of source mapping: find position in inline content
+    /// corresponding to a byte offset in the original source.
+    #[must_use]
+    pub(crate) fn get_inline_pos_for(&self, source_pos: usize) -> usize {
+        let line = match self.srcmap.binary_search_by(|x| x.1.cmp(&source_pos)) {
+            Ok(x) => x,
+            Err(x) => x.saturating_sub(1),
+        };
+        self.srcmap[line].0 + source_pos.saturating_sub(self.srcmap[line].1)
+    }
+
     #[must_use]
     pub fn get_map(&self, start_pos: usize, end_pos: usize) -> Option<SourcePos> {
         debug_assert!(start_pos <= end_pos);
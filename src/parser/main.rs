@@ -1,8 +1,8 @@
 use derivative::Derivative;
+use std::sync::Arc;
 
 use crate::common::ruler::Ruler;
 use crate::common::sourcemap::SourcePos;
-use crate::common::TypeKey;
 use crate::parser::block::{self, BlockParser};
 use crate::parser::core::{Root, *};
 use crate::parser::extset::MarkdownItExtSet;
@@ -10,7 +10,7 @@ use crate::parser::inline::{self, InlineParser};
 use crate::parser::linkfmt::{LinkFormatter, MDLinkFormatter};
 use crate::Node;
 
-type RuleFn = fn (&mut Node, &MarkdownIt);
+type RuleFn = Arc<dyn CoreRule>;
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -39,7 +39,7 @@ pub struct MarkdownIt {
     /// default i32::MAX, indented code blocks will set this to 4
     pub max_indent: i32,
 
-    ruler: Ruler<TypeKey, RuleFn>,
+    ruler: Ruler<RuleId, RuleFn>,
 }
 
 impl MarkdownIt {
@@ -52,23 +52,46 @@ impl MarkdownIt {
         node.srcmap = Some(SourcePos::new(0, src.len()));
 
         for rule in self.ruler.iter() {
-            rule(&mut node, self);
+            rule.run(&mut node, self);
             debug_assert!(node.is::<Root>(), "root node of the AST must always be Root");
         }
         node
     }
 
-    pub fn add_rule<T: CoreRule>(&mut self) -> RuleBuilder<RuleFn> {
-        let item = self.ruler.add(TypeKey::of::<T>(), T::run);
+    /// Add a core rule instance identified by its type.
+    pub fn add_rule<T: CoreRule>(&mut self, rule: T) -> RuleBuilder<'_, RuleFn> {
+        let item = self.ruler.add(RuleId::of::<T>(), Arc::new(rule));
+        RuleBuilder::new(item)
+    }
+
+    /// Add a core rule instance identified by explicit `id` (and also by its type),
+    /// use this if you need multiple differently configured instances of one rule.
+    pub fn add_rule_with_id<T: CoreRule>(&mut self, id: impl Into<RuleId>, rule: T) -> RuleBuilder<'_, RuleFn> {
+        let item = self.ruler.add(id.into(), Arc::new(rule));
+        item.alias(RuleId::of::<T>());
         RuleBuilder::new(item)
     }
 
     pub fn has_rule<T: CoreRule>(&mut self) -> bool {
-        self.ruler.contains(TypeKey::of::<T>())
+        self.ruler.contains(RuleId::of::<T>())
+    }
+
+    pub fn has_rule_id(&mut self, id: impl Into<RuleId>) -> bool {
+        self.ruler.contains(id.into())
+    }
+
+    /// Get mutable access to an already added rule instance
+    /// (returns `None` if rule isn't found or has a different type).
+    pub fn get_rule_mut<T: CoreRule>(&mut self, id: impl Into<RuleId>) -> Option<&mut T> {
+        Arc::get_mut(self.ruler.get_mut(id.into())?)?.downcast_mut::<T>()
     }
 
     pub fn remove_rule<T: CoreRule>(&mut self) {
-        self.ruler.remove(TypeKey::of::<T>());
+        self.ruler.remove(RuleId::of::<T>());
+    }
+
+    pub fn remove_rule_id(&mut self, id: impl Into<RuleId>) {
+        self.ruler.remove(id.into());
     }
 }
 
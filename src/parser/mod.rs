@@ -29,6 +29,12 @@
 //! [md.block.add_rule](block::BlockParser::add_rule) or
 //! [md.add_rule](crate::MarkdownIt::add_rule) respectively.
 //!
+//! Each rule is an instance of a type implementing [CoreRule](core::CoreRule),
+//! [BlockRule](block::BlockRule) or [InlineRule](inline::InlineRule), so it can
+//! carry its own configuration. Rules are identified by their type, or by an explicit
+//! [RuleId](core::RuleId) if you need several differently configured instances
+//! of the same rule (see [md.add_rule_with_id](crate::MarkdownIt::add_rule_with_id)).
+//!
 //! These are examples of the rules in each chain (view source to see implementation):
 //!  - [inline rule](crate::plugins::cmark::inline::autolink) - autolink
 //!  - [block rule](crate::plugins::cmark::block::hr) - thematic break
@@ -36,9 +42,14 @@
 //!
 pub mod block;
 pub mod core;
+pub mod cursor;
+pub mod diagnostics;
+pub mod diff;
 pub mod extset;
+pub mod highlighter;
 pub mod inline;
 pub mod linkfmt;
+pub mod query;
 
 pub(super) mod main;
 pub(super) mod node;
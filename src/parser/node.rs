@@ -2,10 +2,13 @@ use downcast_rs::{impl_downcast, Downcast};
 use std::any::TypeId;
 use std::fmt::Debug;
 
-use crate::common::sourcemap::SourcePos;
+use crate::common::sourcemap::{SourceMap, SourcePos};
 use crate::common::TypeKey;
+use crate::parser::cursor::Cursor;
+use crate::parser::diff::{self, Edit};
 use crate::parser::extset::NodeExtSet;
 use crate::parser::inline::Text;
+use crate::parser::query::{Descendants, Selector, SelectorError, WithAncestors};
 use crate::parser::renderer::HTMLRenderer;
 use crate::plugins::cmark::inline::newline::Softbreak;
 use crate::Renderer;
@@ -89,6 +92,28 @@ impl Node {
         fmt.into()
     }
 
+    /// Render this node to HTML along with a [source map](SourceMap) linking
+    /// the output to `source` markdown (named `source_name` in the map).
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let source = "# hello\n\n*world*";
+    /// let (html, map) = md.parse(source).render_with_source_map(source, "hello.md");
+    /// assert_eq!(html, "<h1>hello</h1>\n<p><em>world</em></p>\n");
+    /// assert_eq!(map.mappings, "AAAA,IAAE,KAAK;AAEP,GAAA,IAAC,KAAK,KAAC");
+    /// ```
+    pub fn render_with_source_map(&self, source: &str, source_name: &str) -> (String, SourceMap) {
+        let mut fmt = HTMLRenderer::<false>::new();
+        fmt.enable_source_map(source);
+        fmt.render(self);
+        let offsets = fmt.source_map_offsets();
+        let html: String = fmt.into();
+        let map = SourceMap::from_offsets(&html, source, source_name, &offsets);
+        (html, map)
+    }
+
     /// Render this node to XHTML, it adds slash to self-closing tags like this: `<img />`.
     ///
     /// This mode exists for compatibility with CommonMark tests.
@@ -182,8 +207,190 @@ impl Node {
 
         result
     }
+
+    /// Deep copy of this node and all its descendants, fails if any of them
+    /// doesn't support cloning (see [NodeValue::clone_value]).
+    ///
+    /// Extensions (`node.ext`) that can't be cloned are skipped.
+    ///
+    /// ```rust
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let mut ast = md.parse("# hello");
+    /// let heading = ast.children[0].try_clone().unwrap();
+    /// ast.children.push(heading);
+    /// assert_eq!(ast.render(), "<h1>hello</h1>\n<h1>hello</h1>\n");
+    /// ```
+    pub fn try_clone(&self) -> Result<Node, CloneError> {
+        let node_value = self.node_value.clone_value()
+            .ok_or(CloneError { node_name: self.name() })?;
+
+        let mut children = Vec::with_capacity(self.children.len());
+        for child in self.children.iter() {
+            children.push(stacker::maybe_grow(64*1024, 1024*1024, || child.try_clone())?);
+        }
+
+        Ok(Node {
+            children,
+            srcmap:     self.srcmap,
+            ext:        self.ext.partial_clone(),
+            attrs:      self.attrs.clone(),
+            node_type:  self.node_type,
+            node_value,
+        })
+    }
+
+    /// Check that `other` tree renders the same html, ignoring formatting
+    /// differences (see [diff](crate::parser::diff)).
+    pub fn structural_eq(&self, other: &Node) -> bool {
+        diff::structural_eq(self, other)
+    }
+
+    /// Find changes between this (old) tree and `other` (new) tree, see [diff](crate::parser::diff).
+    pub fn diff<'a>(&'a self, other: &'a Node) -> Vec<Edit<'a>> {
+        diff::diff(self, other)
+    }
+
+    /// Render `other` (new) tree into html, marking changes since this (old) tree,
+    /// see [diff](crate::parser::diff).
+    pub fn render_diff(&self, other: &Node) -> String {
+        diff::render_tree_diff(self, other)
+    }
+
+    /// Create a [cursor](crate::parser::cursor) for navigating and editing this subtree.
+    pub fn cursor(&mut self) -> Cursor<'_> {
+        Cursor::new(self)
+    }
+
+    /// Iterate over all descendants of this node (not including itself) in preorder.
+    pub fn descendants(&self) -> Descendants<'_> {
+        Descendants::new(self)
+    }
+
+    /// Iterate over all descendants of this node along with their ancestors
+    /// (from this node to the parent of each one).
+    pub fn descendants_with_ancestors(&self) -> WithAncestors<'_> {
+        Descendants::new(self).with_ancestors()
+    }
+
+    /// Iterate over all descendants of type `T` in preorder.
+    pub fn descendants_of<T: NodeValue>(&self) -> impl Iterator<Item = (&Node, &T)> {
+        self.descendants().filter_map(|node| Some((node, node.cast::<T>()?)))
+    }
+
+    /// Iterate over direct children of type `T`.
+    pub fn children_of<T: NodeValue>(&self) -> impl Iterator<Item = (&Node, &T)> {
+        self.children.iter().filter_map(|node| Some((node, node.cast::<T>()?)))
+    }
+
+    /// Find first descendant of type `T` in preorder.
+    pub fn find_first<T: NodeValue>(&self) -> Option<(&Node, &T)> {
+        self.descendants_of::<T>().next()
+    }
+
+    /// Return ancestors of `target` (compared by address), from this node to its parent,
+    /// or `None` if `target` is not a descendant of this node.
+    pub fn ancestors_of(&self, target: &Node) -> Option<Vec<&Node>> {
+        let mut iter = self.descendants();
+        while let Some(node) = iter.next() {
+            if std::ptr::eq(node, target) {
+                return Some(iter.ancestors().collect());
+            }
+        }
+        None
+    }
+
+    /// Find all descendants matching a [selector](crate::parser::query), e.g. `"BulletList > ListItem Link"`.
+    pub fn select(&self, selector: &str) -> Result<Vec<&Node>, SelectorError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+
+    /// Find nodes containing a character at given byte offset in the source,
+    /// returns path from this node to the deepest one (or empty vec if nothing is found).
+    ///
+    /// Nodes without source mapping are included only if their descendant is found.
+    /// To search by line and column, convert them to offset with
+    /// [SourceWithLineStarts::get_byte_offset](crate::common::sourcemap::SourceWithLineStarts::get_byte_offset).
+    ///
+    /// ```rust
+    /// use markdown_it::plugins::cmark::inline::emphasis::Em;
+    ///
+    /// let md = &mut markdown_it::MarkdownIt::new();
+    /// markdown_it::plugins::cmark::add(md);
+    ///
+    /// let ast = md.parse("# title\n\nsome *text*");
+    /// let path = ast.node_at(15);
+    /// assert_eq!(path.len(), 4); // root, paragraph, emphasis, text
+    /// assert!(path[2].is::<Em>());
+    /// ```
+    pub fn node_at(&self, byte_offset: usize) -> Vec<&Node> {
+        fn find<'a>(node: &'a Node, offset: usize, path: &mut Vec<&'a Node>) -> bool {
+            if let Some(map) = node.srcmap {
+                let (start, end) = map.get_byte_offsets();
+                if offset < start || offset >= end { return false; }
+            }
+
+            path.push(node);
+            for child in node.children.iter() {
+                let found = stacker::maybe_grow(64*1024, 1024*1024, || find(child, offset, path));
+                if found { return true; }
+            }
+
+            if node.srcmap.is_none() {
+                path.pop();
+                return false;
+            }
+            true
+        }
+
+        let mut path = Vec::new();
+        find(self, byte_offset, &mut path);
+        path
+    }
+
+    /// Find all nodes with source mapping overlapping byte range `start..end`
+    /// (in preorder, i.e. parents before their children); empty range finds
+    /// all nodes containing `start`.
+    pub fn nodes_in_range(&self, start: usize, end: usize) -> Vec<&Node> {
+        fn find<'a>(node: &'a Node, range: (usize, usize), result: &mut Vec<&'a Node>) {
+            if let Some(map) = node.srcmap {
+                let (start, end) = map.get_byte_offsets();
+                let overlaps = if range.0 == range.1 {
+                    start <= range.0 && range.0 < end
+                } else {
+                    start < range.1 && range.0 < end
+                };
+                if !overlaps { return; }
+                result.push(node);
+            }
+
+            for child in node.children.iter() {
+                stacker::maybe_grow(64*1024, 1024*1024, || find(child, range, result));
+            }
+        }
+
+        let mut result = Vec::new();
+        find(self, (start, end), &mut result);
+        result
+    }
+}
+
+/// Error returned by [Node::try_clone] if some node value can't be cloned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloneError {
+    /// Type name of that value.
+    pub node_name: &'static str,
+}
+
+impl std::fmt::Display for CloneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} doesn't implement clone_value", self.node_name)
+    }
 }
 
+impl std::error::Error for CloneError {}
+
 impl Drop for Node {
     fn drop(&mut self) {
         self.walk_post_mut(|node, _| {
@@ -192,10 +399,12 @@ impl Drop for Node {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[doc(hidden)]
 pub struct NodeEmpty;
-impl NodeValue for NodeEmpty {}
+impl NodeValue for NodeEmpty {
+    crate::impl_clone_value!();
+}
 
 impl Default for Node {
     /// Create empty Node. Empty node should only be used as placeholder for functions like
@@ -224,6 +433,37 @@ pub trait NodeValue : Debug + Downcast {
         let _ = fmt;
         unimplemented!("{} doesn't implement render", node.name());
     }
+
+    /// Copy this value for [Node::try_clone], `None` if it can't be copied (default).
+    ///
+    /// Types implementing `Clone` should override it with [impl_clone_value](crate::impl_clone_value).
+    fn clone_value(&self) -> Option<Box<dyn NodeValue>> {
+        None
+    }
 }
 
 impl_downcast!(NodeValue);
+
+/// Implement [NodeValue::clone_value] for a type implementing `Clone`,
+/// use it inside of `impl NodeValue` block.
+///
+/// ```rust
+/// use markdown_it::{Node, NodeValue};
+///
+/// #[derive(Debug, Clone)]
+/// struct Marker(u32);
+/// impl NodeValue for Marker {
+///     markdown_it::impl_clone_value!();
+/// }
+///
+/// let node = Node::new(Marker(42));
+/// assert_eq!(node.try_clone().unwrap().cast::<Marker>().unwrap().0, 42);
+/// ```
+#[macro_export]
+macro_rules! impl_clone_value {
+    () => {
+        fn clone_value(&self) -> Option<Box<dyn $crate::NodeValue>> {
+            Some(Box::new(::std::clone::Clone::clone(self)))
+        }
+    };
+}
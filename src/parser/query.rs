@@ -0,0 +1,237 @@
+//! Iterators over syntax tree and selectors matching nodes by type name.
+//!
+//! Instead of writing [walk](Node::walk) closures, you can iterate over
+//! [descendants](Node::descendants) of a node, or over nodes of one type with
+//! [descendants_of](Node::descendants_of):
+//!
+//! ```rust
+//! use markdown_it::plugins::cmark::inline::link::Link;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("[foo](/a) and *[bar](/b)*");
+//! let urls: Vec<_> = ast.descendants_of::<Link>().map(|(_, link)| link.url.as_str()).collect();
+//! assert_eq!(urls, ["/a", "/b"]);
+//! ```
+//!
+//! Selectors are a small subset of css: node type names (like `BulletList`, or full
+//! paths like `cmark::block::list::BulletList`), `*` for any node, and combinators
+//! `A B` (B is a descendant of A) and `A > B` (B is a child of A):
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//!
+//! let ast = md.parse("- [foo](/a)\n\n1. [bar](/b)");
+//! let links = ast.select("BulletList > ListItem Link").unwrap();
+//! assert_eq!(links.len(), 1);
+//! assert_eq!(links[0].collect_text(), "foo");
+//! ```
+use std::fmt;
+
+use crate::Node;
+
+/// Preorder iterator over descendants of a node, created by [Node::descendants].
+#[derive(Debug)]
+pub struct Descendants<'a> {
+    stack: Vec<(&'a Node, std::slice::Iter<'a, Node>)>,
+}
+
+impl<'a> Descendants<'a> {
+    pub(crate) fn new(node: &'a Node) -> Self {
+        Self { stack: vec![(node, node.children.iter())] }
+    }
+
+    /// Turn this into iterator that also yields ancestors of each node.
+    pub fn with_ancestors(self) -> WithAncestors<'a> {
+        WithAncestors(self)
+    }
+
+    fn advance(&mut self) -> Option<&'a Node> {
+        loop {
+            let (_, iter) = self.stack.last_mut()?;
+            if let Some(child) = iter.next() {
+                self.stack.push((child, child.children.iter()));
+                return Some(child);
+            }
+            self.stack.pop();
+        }
+    }
+
+    /// Ancestors of the last returned node, from the starting node to its parent.
+    pub(crate) fn ancestors(&self) -> impl Iterator<Item = &'a Node> + '_ {
+        self.stack[..self.stack.len() - 1].iter().map(|(node, _)| *node)
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.advance()
+    }
+}
+
+/// Iterator yielding each descendant along with its ancestors (from the starting
+/// node to the parent), created by [Node::descendants_with_ancestors].
+#[derive(Debug)]
+pub struct WithAncestors<'a>(Descendants<'a>);
+
+impl<'a> Iterator for WithAncestors<'a> {
+    type Item = (&'a Node, Vec<&'a Node>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.0.advance()?;
+        Some((node, self.0.ancestors().collect()))
+    }
+}
+
+/// Error returned when selector can't be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorError {
+    /// Byte offset in the selector string.
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SelectorError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+/// Parsed selector, see [module documentation](self) for syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    // first item has no combinator
+    parts: Vec<(Combinator, String)>,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorError> {
+        let mut parts = Vec::new();
+        let mut combinator = None;
+        let mut pos = 0;
+
+        while pos < selector.len() {
+            let rest = &selector[pos..];
+            let ch = rest.chars().next().unwrap();
+
+            if ch.is_whitespace() {
+                pos += ch.len_utf8();
+            } else if ch == '>' {
+                if parts.is_empty() || combinator.is_some() {
+                    return Err(SelectorError { position: pos, message: "unexpected `>`" });
+                }
+                combinator = Some(Combinator::Child);
+                pos += 1;
+            } else {
+                let len = rest.find(|c: char| c.is_whitespace() || c == '>').unwrap_or(rest.len());
+                let name = &rest[..len];
+                if !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == ':' || c == '*') {
+                    return Err(SelectorError { position: pos, message: "invalid type name" });
+                }
+                parts.push((combinator.take().unwrap_or(Combinator::Descendant), name.to_owned()));
+                pos += len;
+            }
+        }
+
+        if parts.is_empty() {
+            return Err(SelectorError { position: 0, message: "empty selector" });
+        }
+        if combinator.is_some() {
+            return Err(SelectorError { position: selector.len(), message: "selector ends with `>`" });
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Check if `node` matches this selector, `ancestors` go from the root to its parent.
+    pub fn matches(&self, node: &Node, ancestors: &[&Node]) -> bool {
+        let mut path = ancestors.to_vec();
+        path.push(node);
+
+        // results for each (part, depth) pair, so descendant combinators
+        // don't check the same ancestors over and over again
+        let mut memo = vec![None; self.parts.len() * path.len()];
+        self.matches_part(self.parts.len() - 1, path.len() - 1, &path, &mut memo)
+    }
+
+    /// Find all descendants of `root` matching this selector, in preorder.
+    pub fn select<'a>(&self, root: &'a Node) -> Vec<&'a Node> {
+        let mut result = Vec::new();
+        let mut iter = root.descendants();
+        while let Some(node) = iter.advance() {
+            let ancestors: Vec<_> = iter.ancestors().collect();
+            if self.matches(node, &ancestors) {
+                result.push(node);
+            }
+        }
+        result
+    }
+
+    /// Check if `path[depth]` matches selector up to part `idx`.
+    fn matches_part(&self, idx: usize, depth: usize, path: &[&Node], memo: &mut [Option<bool>]) -> bool {
+        let key = idx * path.len() + depth;
+        if let Some(result) = memo[key] { return result; }
+
+        let (combinator, name) = &self.parts[idx];
+        let result = type_name_matches(name, path[depth].name()) && (idx == 0 || match combinator {
+            Combinator::Child => depth > 0 && self.matches_part(idx - 1, depth - 1, path, memo),
+            Combinator::Descendant => (0..depth).rev().any(|i| self.matches_part(idx - 1, i, path, memo)),
+        });
+
+        memo[key] = Some(result);
+        result
+    }
+}
+
+/// Compare selector part with full type name (e.g. `markdown_it::...::BulletList`).
+fn type_name_matches(pattern: &str, type_name: &str) -> bool {
+    if pattern == "*" { return true; }
+
+    // strip generic parameters
+    let type_name = &type_name[..type_name.find('<').unwrap_or(type_name.len())];
+
+    if pattern.contains("::") {
+        type_name == pattern || type_name.ends_with(&format!("::{}", pattern))
+    } else {
+        type_name.rsplit("::").next() == Some(pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Combinator, Selector, SelectorError};
+
+    #[test]
+    fn parse_selector() {
+        let selector = Selector::parse(" BulletList>ListItem  list::Link ").unwrap();
+        assert_eq!(selector.parts, [
+            (Combinator::Descendant, "BulletList".to_owned()),
+            (Combinator::Child, "ListItem".to_owned()),
+            (Combinator::Descendant, "list::Link".to_owned()),
+        ]);
+
+        let selector = Selector::parse("A > * B").unwrap();
+        assert_eq!(selector.parts.len(), 3);
+    }
+
+    #[test]
+    fn parse_selector_errors() {
+        assert_eq!(Selector::parse("  ").unwrap_err().message, "empty selector");
+        assert_eq!(Selector::parse("> A").unwrap_err(), SelectorError { position: 0, message: "unexpected `>`" });
+        assert_eq!(Selector::parse("A > > B").unwrap_err().position, 4);
+        assert_eq!(Selector::parse("A >").unwrap_err().message, "selector ends with `>`");
+        assert_eq!(Selector::parse("A.b").unwrap_err().message, "invalid type name");
+    }
+}
@@ -33,6 +33,18 @@ pub trait Renderer {
 pub(crate) struct HTMLRenderer<const XHTML: bool> {
     result: String,
     ext: RenderExtSet,
+    source_map: Option<SourceMapOffsets>,
+}
+
+#[derive(Debug, Default)]
+/// Positions recorded while rendering, see [HTMLRenderer::enable_source_map].
+struct SourceMapOffsets {
+    /// Markdown source of the document being rendered.
+    source: String,
+    /// (output offset, source offset) pairs.
+    offsets: Vec<(usize, usize)>,
+    /// Number of nodes with source positions currently being rendered.
+    depth: usize,
 }
 
 impl<const XHTML: bool> HTMLRenderer<XHTML> {
@@ -40,11 +52,95 @@ impl<const XHTML: bool> HTMLRenderer<XHTML> {
         Self {
             result: String::new(),
             ext: RenderExtSet::new(),
+            source_map: None,
         }
     }
 
+    /// Record positions of rendered nodes in `source`, use [source_map_offsets](Self::source_map_offsets)
+    /// to get them after rendering.
+    pub fn enable_source_map(&mut self, source: &str) {
+        self.source_map = Some(SourceMapOffsets { source: source.to_owned(), ..Default::default() });
+    }
+
+    /// Pairs of (output offset, source offset), each one marks the start of the output
+    /// of a node, a line inside of it, or the rest of its parent after it (which is
+    /// mapped to the end of the node).
+    pub fn source_map_offsets(&self) -> Vec<(usize, usize)> {
+        let Some(source_map) = &self.source_map else { return Vec::new(); };
+        if !self.result.contains('\0') { return source_map.offsets.clone(); }
+
+        // account for U+0000 replacement done in `into()`
+        let nulls: Vec<_> = self.result.match_indices('\0').map(|(pos, _)| pos).collect();
+        let extra = '\u{FFFD}'.len_utf8() - 1;
+        source_map.offsets.iter().map(|&(output, source)| {
+            (output + nulls.partition_point(|&pos| pos < output) * extra, source)
+        }).collect()
+    }
+
     pub fn render(&mut self, node: &Node) {
+        let source_pos = node.srcmap.filter(|_| self.source_map.is_some());
+        let output_start = self.result.len();
+
+        if let Some(pos) = source_pos {
+            let source_map = self.source_map.as_mut().unwrap();
+            source_map.offsets.push((output_start, pos.get_byte_offsets().0));
+            source_map.depth += 1;
+        }
+
         node.node_value.render(node, self);
+
+        if let Some(pos) = source_pos {
+            let (start, end) = pos.get_byte_offsets();
+            if node.children.is_empty() {
+                self.map_inner_lines(output_start, start, end);
+            }
+
+            let source_map = self.source_map.as_mut().unwrap();
+            source_map.depth -= 1;
+            if source_map.depth > 0 {
+                source_map.offsets.push((self.result.len(), end));
+            }
+        }
+    }
+
+    /// Map each line of a multi-line leaf node (code blocks, html, etc.) to the source line
+    /// it is copied from. Lines are matched by content, so that lines added by the node
+    /// itself (e.g. fence markers) are skipped.
+    fn map_inner_lines(&mut self, output_start: usize, source_start: usize, source_end: usize) {
+        let source_map = self.source_map.as_mut().unwrap();
+        let output = &self.result[output_start..];
+        if !output.trim_end_matches('\n').contains('\n') { return; }
+
+        let Some(source) = source_map.source.get(source_start..source_end) else { return; };
+        let source_lines: Vec<_> = source.match_indices('\n')
+            .map(|(pos, _)| {
+                let line = source[pos + 1..].split('\n').next().unwrap();
+                (source_start + pos + 1, line.trim_end_matches('\r'))
+            })
+            .collect();
+        let mut next_line = 0;
+
+        for (pos, _) in output.match_indices('\n') {
+            let line = output[pos + 1..].split('\n').next().unwrap();
+            if line.is_empty() { continue; }
+
+            // source line may have extra indentation and unescaped characters,
+            // only look a couple of lines ahead, so that unmatched (e.g. highlighted) code stays fast
+            let indent_of = |text: &str| {
+                text.strip_suffix(line).filter(|prefix| prefix.trim_start().is_empty()).map(str::len)
+            };
+            let found = source_lines[next_line..].iter().take(2).enumerate().find_map(|(idx, &(offset, source_line))| {
+                let indent = indent_of(source_line).or_else(|| indent_of(&escape_html(source_line)))?;
+                Some((idx, offset + indent))
+            });
+
+            if let Some((idx, source_offset)) = found {
+                source_map.offsets.push((output_start + pos + 1, source_offset));
+                next_line += idx + 1;
+            } else {
+                next_line = (next_line + 1).min(source_lines.len());
+            }
+        }
     }
 
     fn make_attr(&mut self, name: &str, value: &str) {
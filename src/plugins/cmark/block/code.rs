@@ -3,41 +3,42 @@
 //! Parses anything indented with 4 spaces.
 //!
 //! <https://spec.commonmark.org/0.30/#indented-code-block>
-use crate::parser::block::{BlockRule, BlockState};
+use std::sync::Arc;
+
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
+use crate::parser::highlighter::{self, CodeBlockInfo, Highlighter};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
 const CODE_INDENT: i32 = 4;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CodeBlock {
     pub content: String,
+    /// Highlighter used to render this block (see [highlighter]).
+    pub highlighter: Option<Arc<dyn Highlighter>>,
 }
 
 impl NodeValue for CodeBlock {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
-        fmt.cr();
-        fmt.open("pre", &[]);
-            fmt.open("code", &node.attrs);
-            fmt.text(&self.content);
-            fmt.close("code");
-        fmt.close("pre");
-        fmt.cr();
+        highlighter::render_code_block(fmt, self.highlighter.as_deref(), &CodeBlockInfo::default(), &self.content, &node.attrs);
     }
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<CodeScanner>();
+    md.block.add_rule(CodeScanner);
     md.max_indent = CODE_INDENT;
 }
 
 #[doc(hidden)]
 pub struct CodeScanner;
 impl BlockRule for CodeScanner {
-    fn check(_: &mut BlockState) -> Option<()> {
-        None
+    fn can_interrupt(&self, _: InterruptTarget) -> bool {
+        false
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         if state.line_indent(state.line) < CODE_INDENT { return None; }
 
         let mut next_line = state.line + 1;
@@ -61,7 +62,10 @@ impl BlockRule for CodeScanner {
         let (mut content, _mapping) = state.get_lines(state.line, last, CODE_INDENT as usize + state.blk_indent, false);
         content += "\n";
 
-        let node = Node::new(CodeBlock { content });
+        let node = Node::new(CodeBlock {
+            content,
+            highlighter: highlighter::get_highlighter(state.md),
+        });
         //node.srcmap = state.get_map_from_offsets(mapping[0].1, state.line_offsets[last - 1].line_end);
 
         Some((node, last - state.line))
@@ -3,41 +3,248 @@
 //! ` ```lang ` or `~~~lang`
 //!
 //! <https://spec.commonmark.org/0.30/#code-fence>
+//!
+//! Info string can be parsed into [FenceInfo], which understands common conventions
+//! used by static site generators. Rendering them is opt-in with [enable_extended_info]
+//! (otherwise only the first word is used as a language, as in CommonMark):
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::cmark::block::fence::enable_extended_info(md);
+//!
+//! let html = md.parse("```rust title=\"main.rs\" {2} {#main .example} showLineNumbers\nfn main() {\n}\n```").render();
+//! assert_eq!(html, "\
+//! <figure class=\"code-block\">
+//! <figcaption>main.rs</figcaption>
+//! <pre><code class=\"language-rust example\" id=\"main\">\
+//! <span class=\"line\"><span class=\"line-number\">1</span>fn main() {</span>
+//! <span class=\"line highlighted\"><span class=\"line-number\">2</span>}</span>
+//! </code></pre>
+//! </figure>
+//! ");
+//! ```
+use derivative::Derivative;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
 use crate::common::utils::unescape_all;
 use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::diagnostics::{Diagnostic, Diagnostics};
 use crate::parser::extset::MarkdownItExt;
+use crate::parser::highlighter::{self, CodeBlockInfo, Highlighter};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CodeFence {
     pub info: String,
     pub marker: char,
     pub marker_len: usize,
     pub content: String,
     pub lang_prefix: &'static str,
+    /// Highlighter used to render this fence (see [highlighter]).
+    pub highlighter: Option<Arc<dyn Highlighter>>,
+    /// Render attributes, line numbers and caption from info string
+    /// (see [enable_extended_info]).
+    pub extended_info: bool,
+}
+
+impl CodeFence {
+    /// Parse info string, see [FenceInfo::parse].
+    pub fn parse_info(&self) -> FenceInfo {
+        FenceInfo::parse(&self.info)
+    }
+
+    /// Language name, i.e. first word of the info string (if any).
+    pub fn lang(&self) -> Option<String> {
+        let info = unescape_all(&self.info);
+        info.split_whitespace().next().map(|lang| lang.to_owned())
+    }
 }
 
 impl NodeValue for CodeFence {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
-        let info = unescape_all(&self.info);
-        let mut split = info.split_whitespace();
-        let lang_name = split.next().unwrap_or("");
+        let info = if self.extended_info {
+            self.parse_info()
+        } else {
+            FenceInfo { lang: self.lang(), ..Default::default() }
+        };
         let mut attrs = node.attrs.clone();
-        let class;
 
-        if !lang_name.is_empty() {
-            class = format!("{}{}", self.lang_prefix, lang_name);
-            attrs.push(("class", class));
+        if let Some(lang) = &info.lang {
+            attrs.push(("class", format!("{}{}", self.lang_prefix, lang)));
+        }
+
+        for (name, value) in info.attrs.iter() {
+            match name.as_str() {
+                "class" => attrs.push(("class", value.clone())),
+                "id" => attrs.push(("id", value.clone())),
+                _ => {}
+            }
+        }
+
+        highlighter::render_code_block(fmt, self.highlighter.as_deref(), &info.as_code_block_info(), &self.content, &attrs);
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// Parsed info string of a code fence.
+///
+/// Info string is split into words (values can be quoted), where:
+///  - first word is a language, unless it's an attribute or a `{...}` block
+///  - `key=value` words are attributes
+///  - `{1,3-5}` lists lines to highlight
+///  - `{.class #id key=value flag}` adds classes, id and other attributes (like
+///    in pandoc), `.class` and `#id` are added to the `<code>` element
+///  - all other words are flags (e.g. `showLineNumbers`)
+///
+/// If [enabled](enable_extended_info), renderer shows line numbers if `showLineNumbers`
+/// or `linenos` flag is present, and `title` (or `filename`) attribute as a caption.
+pub struct FenceInfo {
+    /// Language name, e.g. `rust`.
+    pub lang: Option<String>,
+    /// Attributes in order of appearance, classes are joined by spaces.
+    pub attrs: Vec<(String, String)>,
+    /// Words without values.
+    pub flags: Vec<String>,
+    /// Ranges of lines (starting from 1) to highlight.
+    pub lines: Vec<RangeInclusive<usize>>,
+}
+
+impl FenceInfo {
+    /// Parse an info string (backslash escapes and entities are decoded first).
+    pub fn parse(info: &str) -> Self {
+        let info = unescape_all(info);
+        let mut result = Self::default();
+        let mut rest = info.trim();
+        let mut first = true;
+
+        while !rest.is_empty() {
+            if let Some(inner) = rest.strip_prefix('{') {
+                let end = inner.find('}').unwrap_or(inner.len());
+                result.add_block(&inner[..end]);
+                rest = inner.get(end + 1..).unwrap_or("");
+            } else {
+                let (word, tail) = next_word(rest);
+                if first && !word.contains('=') {
+                    result.lang = Some(word.to_owned());
+                } else {
+                    result.add_word(word);
+                }
+                rest = tail;
+            }
+            first = false;
+            rest = rest.trim_start();
+        }
+
+        result
+    }
+
+    /// Value of an attribute, if present.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Check if a flag is present.
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// Caption to show above the code, from `title` or `filename` attribute.
+    pub fn title(&self) -> Option<&str> {
+        self.attr("title").or_else(|| self.attr("filename"))
+    }
+
+    /// Whether line numbers should be shown.
+    pub fn show_line_numbers(&self) -> bool {
+        self.has_flag("showLineNumbers") || self.has_flag("linenos")
+    }
+
+    /// Check if line (starting from 1) should be highlighted.
+    pub fn is_highlighted(&self, line: usize) -> bool {
+        self.lines.iter().any(|range| range.contains(&line))
+    }
+
+    /// Options for [highlighter::render_code_block].
+    pub fn as_code_block_info(&self) -> CodeBlockInfo<'_> {
+        CodeBlockInfo {
+            lang: self.lang.as_deref(),
+            title: self.title(),
+            line_numbers: self.show_line_numbers(),
+            highlighted_lines: &self.lines,
         }
+    }
+
+    fn add_word(&mut self, word: &str) {
+        match word.split_once('=') {
+            Some((key, value)) => self.set_attr(key, unquote(value)),
+            None => self.flags.push(word.to_owned()),
+        }
+    }
+
+    fn set_attr(&mut self, key: &str, value: &str) {
+        if key == "class" {
+            if let Some((_, classes)) = self.attrs.iter_mut().find(|(key, _)| key == "class") {
+                classes.push(' ');
+                classes.push_str(value);
+                return;
+            }
+        }
+        self.attrs.push((key.to_owned(), value.to_owned()));
+    }
+
+    fn add_block(&mut self, block: &str) {
+        if block.contains(|c: char| c.is_ascii_digit()) &&
+           block.chars().all(|c| c.is_ascii_digit() || matches!(c, ',' | '-' | ' ')) {
+            for range in block.split(',') {
+                let (start, end) = range.split_once('-').unwrap_or((range, range));
+                if let (Ok(start), Ok(end)) = (start.trim().parse(), end.trim().parse()) {
+                    self.lines.push(start..=end);
+                }
+            }
+            return;
+        }
+
+        let mut rest = block.trim();
+        while !rest.is_empty() {
+            let (word, tail) = next_word(rest);
+            if let Some(class) = word.strip_prefix('.') {
+                self.set_attr("class", class);
+            } else if let Some(id) = word.strip_prefix('#') {
+                self.set_attr("id", id);
+            } else {
+                self.add_word(word);
+            }
+            rest = tail.trim_start();
+        }
+    }
+}
+
+/// Split off first word, which ends at whitespace or `{` outside of quotes.
+fn next_word(str: &str) -> (&str, &str) {
+    let mut quote = None;
+    for (pos, ch) in str.char_indices() {
+        match quote {
+            Some(q) if ch == q => quote = None,
+            Some(_) => {}
+            None if ch == '"' || ch == '\'' => quote = Some(ch),
+            None if ch.is_whitespace() || (ch == '{' && pos > 0) => return str.split_at(pos),
+            None => {}
+        }
+    }
+    (str, "")
+}
 
-        fmt.cr();
-        fmt.open("pre", &[]);
-            fmt.open("code", &attrs);
-            fmt.text(&self.content);
-            fmt.close("code");
-        fmt.close("pre");
-        fmt.cr();
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(value) = value.strip_prefix(quote).and_then(|v| v.strip_suffix(quote)) {
+            return value;
+        }
     }
+    value
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -51,13 +258,76 @@ impl Default for FenceSettings {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<FenceScanner>();
+    md.block.add_rule(FenceScanner);
 }
 
 pub fn set_lang_prefix(md: &mut MarkdownIt, lang_prefix: &'static str) {
     md.ext.insert(FenceSettings(lang_prefix));
 }
 
+pub(crate) fn get_lang_prefix(md: &MarkdownIt) -> &'static str {
+    md.ext.get::<FenceSettings>().copied().unwrap_or_default().0
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ExtendedInfo;
+impl MarkdownItExt for ExtendedInfo {}
+
+/// Render attributes, highlighted lines, line numbers and captions from
+/// info strings of fences parsed after this call (see [FenceInfo]).
+pub fn enable_extended_info(md: &mut MarkdownIt) {
+    md.ext.insert(ExtendedInfo);
+}
+
+type FenceHandlerFn = Box<dyn Fn (&CodeFence, &mut BlockState) -> Option<Node> + Send + Sync>;
+
+#[derive(Default, Derivative)]
+#[derivative(Debug)]
+struct FenceHandlers(#[derivative(Debug = "ignore")] HashMap<String, FenceHandlerFn>);
+impl MarkdownItExt for FenceHandlers {}
+
+/// Turn fences with language `lang` (e.g. `mermaid`) into custom nodes.
+///
+/// Function `f` receives parsed fence and may return a node to be used instead
+/// (or `None` to keep the fence). Such fences are never highlighted, see
+/// [fence_handlers](crate::plugins::extra::fence_handlers) for some examples.
+///
+/// Block state passed to `f` is limited to the lines of fence content, so their
+/// markdown can be parsed in place with `state.md.block.tokenize(state)`.
+///
+/// ```rust
+/// use markdown_it::plugins::cmark::block::fence;
+/// use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+/// use markdown_it::parser::inline::Text;
+/// use markdown_it::Node;
+///
+/// let md = &mut markdown_it::MarkdownIt::new();
+/// markdown_it::plugins::cmark::add(md);
+/// fence::add_handler(md, "upper", |fence, _| {
+///     let mut node = Node::new(Paragraph);
+///     node.children.push(Node::new(Text { content: fence.content.to_uppercase() }));
+///     Some(node)
+/// });
+///
+/// let html = md.parse("```upper\nhello\n```").render();
+/// assert_eq!(html.trim(), "<p>HELLO\n</p>");
+/// ```
+pub fn add_handler(
+    md: &mut MarkdownIt,
+    lang: impl Into<String>,
+    f: impl Fn (&CodeFence, &mut BlockState) -> Option<Node> + Send + Sync + 'static,
+) {
+    let handlers = md.ext.get_or_insert_default::<FenceHandlers>();
+    handlers.0.insert(lang.into(), Box::new(f));
+}
+
+/// Remove a handler added with [add_handler].
+pub fn remove_handler(md: &mut MarkdownIt, lang: &str) {
+    if let Some(handlers) = md.ext.get_mut::<FenceHandlers>() {
+        handlers.0.remove(lang);
+    }
+}
+
 #[doc(hidden)]
 pub struct FenceScanner;
 
@@ -87,11 +357,11 @@ impl FenceScanner {
 }
 
 impl BlockRule for FenceScanner {
-    fn check(state: &mut BlockState) -> Option<()> {
+    fn check(&self, state: &mut BlockState) -> Option<()> {
         Self::get_header(state).map(|_| ())
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         let (marker, len, params) = Self::get_header(state)?;
         let params = params.to_owned();
 
@@ -147,18 +417,51 @@ impl BlockRule for FenceScanner {
             }
         }
 
+        if !have_end_marker {
+            let srcmap = state.get_map(state.line, state.line);
+            state.root_ext.get_or_insert_default::<Diagnostics>().push(Diagnostic::warning(
+                "unclosed-fence",
+                "code fence is not closed",
+                srcmap,
+            ));
+        }
+
         // If a fence has heading spaces, they should be removed from its inner block
         let indent = state.line_offsets[state.line].indent_nonspace;
         let (content, _) = state.get_lines(state.line + 1, next_line, indent as usize, true);
 
-        let lang_prefix = state.md.ext.get::<FenceSettings>().copied().unwrap_or_default().0;
-        let node = Node::new(CodeFence {
+        let lang_prefix = get_lang_prefix(state.md);
+        let fence = CodeFence {
             info: params,
             marker,
             marker_len: len,
             content,
             lang_prefix,
-        });
-        Some((node, next_line - state.line + if have_end_marker { 1 } else { 0 }))
+            highlighter: highlighter::get_highlighter(state.md),
+            extended_info: state.md.ext.get::<ExtendedInfo>().is_some(),
+        };
+        let line_count = next_line - state.line + if have_end_marker { 1 } else { 0 };
+
+        let md = state.md;
+        let handler = md.ext.get::<FenceHandlers>().and_then(|handlers| handlers.0.get(&fence.lang()?));
+        if let Some(f) = handler {
+            let start_line = state.line;
+            let old_line_max = state.line_max;
+            let old_indent = state.blk_indent;
+
+            state.line = start_line + 1;
+            state.line_max = next_line;
+            state.blk_indent = indent as usize;
+            let node = f(&fence, state);
+            state.line = start_line;
+            state.line_max = old_line_max;
+            state.blk_indent = old_indent;
+
+            if let Some(node) = node {
+                return Some((node, line_count));
+            }
+        }
+
+        Some((Node::new(fence), line_count))
     }
 }
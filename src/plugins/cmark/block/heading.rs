@@ -7,12 +7,14 @@ use crate::parser::block::{BlockRule, BlockState};
 use crate::parser::inline::InlineRoot;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ATXHeading {
     pub level: u8,
 }
 
 impl NodeValue for ATXHeading {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         static TAG : [&str; 6] = [ "h1", "h2", "h3", "h4", "h5", "h6" ];
         debug_assert!(self.level >= 1 && self.level <= 6);
@@ -26,13 +28,13 @@ impl NodeValue for ATXHeading {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<HeadingScanner>();
+    md.block.add_rule(HeadingScanner);
 }
 
 #[doc(hidden)]
 pub struct HeadingScanner;
 impl BlockRule for HeadingScanner {
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
 
         if state.line_indent(state.line) >= state.md.max_indent { return None; }
 
@@ -6,13 +6,15 @@
 use crate::parser::block::{BlockRule, BlockState};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ThematicBreak {
     pub marker: char,
     pub marker_len: usize,
 }
 
 impl NodeValue for ThematicBreak {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
         fmt.self_close("hr", &node.attrs);
@@ -21,13 +23,13 @@ impl NodeValue for ThematicBreak {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<HrScanner>();
+    md.block.add_rule(HrScanner);
 }
 
 #[doc(hidden)]
 pub struct HrScanner;
 impl BlockRule for HrScanner {
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
 
         if state.line_indent(state.line) >= state.md.max_indent { return None; }
 
@@ -3,18 +3,20 @@
 //! Paragraph underlined with `===` or `---`.
 //!
 //! <https://spec.commonmark.org/0.30/#setext-headings>
-use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
 use crate::parser::inline::InlineRoot;
 use crate::plugins::cmark::block::paragraph::ParagraphScanner;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SetextHeader {
     pub level: u8,
     pub marker: char,
 }
 
 impl NodeValue for SetextHeader {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         static TAG : [&str; 2] = [ "h1", "h2" ];
         debug_assert!(self.level >= 1 && self.level <= 2);
@@ -28,7 +30,7 @@ impl NodeValue for SetextHeader {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<LHeadingScanner>()
+    md.block.add_rule(LHeadingScanner)
         .before::<ParagraphScanner>()
         .after_all();
 }
@@ -36,11 +38,11 @@ pub fn add(md: &mut MarkdownIt) {
 #[doc(hidden)]
 pub struct LHeadingScanner;
 impl BlockRule for LHeadingScanner {
-    fn check(_: &mut BlockState) -> Option<()> {
-        None // can't interrupt any tags
+    fn can_interrupt(&self, _: InterruptTarget) -> bool {
+        false
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
 
         if state.line_indent(state.line) >= state.md.max_indent { return None; }
 
@@ -78,7 +80,7 @@ impl BlockRule for LHeadingScanner {
             // Some tags can terminate paragraph without empty line.
             let old_state_line = state.line;
             state.line = next_line;
-            if state.test_rules_at_line() {
+            if state.test_rules_at_line(InterruptTarget::Paragraph) {
                 state.line = old_state_line;
                 break 'outer;
             }
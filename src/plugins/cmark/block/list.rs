@@ -7,18 +7,20 @@
 //!  - <https://spec.commonmark.org/0.30/#lists>
 //!  - <https://spec.commonmark.org/0.30/#list-items>
 use crate::common::utils::find_indent_of;
-use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
 use crate::plugins::cmark::block::hr::HrScanner;
 use crate::plugins::cmark::block::paragraph::Paragraph;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OrderedList {
     pub start: u32,
     pub marker: char,
 }
 
 impl NodeValue for OrderedList {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
         let start;
@@ -36,12 +38,14 @@ impl NodeValue for OrderedList {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BulletList {
     pub marker: char,
 }
 
 impl NodeValue for BulletList {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
         fmt.open("ul", &node.attrs);
@@ -53,10 +57,12 @@ impl NodeValue for BulletList {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ListItem;
 
 impl NodeValue for ListItem {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.open("li", &node.attrs);
         fmt.contents(&node.children);
@@ -66,7 +72,7 @@ impl NodeValue for ListItem {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<ListScanner>()
+    md.block.add_rule(ListScanner)
         .after::<HrScanner>();
 }
 
@@ -206,13 +212,16 @@ impl ListScanner {
 }
 
 impl BlockRule for ListScanner {
-    fn check(state: &mut BlockState) -> Option<()> {
-        if state.node.is::<BulletList>() || state.node.is::<OrderedList>() { return None; }
-
+    fn check(&self, state: &mut BlockState) -> Option<()> {
         Self::find_marker(state, true).map(|_| ())
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn can_interrupt(&self, target: InterruptTarget) -> bool {
+        // next list item is handled by the list itself
+        target != InterruptTarget::List
+    }
+
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         let (mut pos_after_marker, marker_value, marker_char) = Self::find_marker(state, false)?;
 
         let new_node = if let Some(int) = marker_value {
@@ -297,7 +306,9 @@ impl BlockRule for ListScanner {
                 }
             } else {
                 state.line = next_line;
+                state.level += 1;
                 state.md.block.tokenize(state);
+                state.level -= 1;
             }
 
             // If any of list item is tight, mark list as tight
@@ -330,7 +341,7 @@ impl BlockRule for ListScanner {
             if state.line_indent(next_line) >= state.md.max_indent { break; }
 
             // fail if terminating block found
-            if state.test_rules_at_line() { break; }
+            if state.test_rules_at_line(InterruptTarget::List) { break; }
 
             current_line = state.get_line(state.line).to_owned();
 
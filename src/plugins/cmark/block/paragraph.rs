@@ -3,19 +3,21 @@
 //! This is the default rule if nothing else matches.
 //!
 //! <https://spec.commonmark.org/0.30/#paragraph>
-use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
 use crate::parser::inline::InlineRoot;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<ParagraphScanner>()
+    md.block.add_rule(ParagraphScanner)
         .after_all();
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Paragraph;
 
 impl NodeValue for Paragraph {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
         fmt.open("p", &node.attrs);
@@ -28,11 +30,11 @@ impl NodeValue for Paragraph {
 #[doc(hidden)]
 pub struct ParagraphScanner;
 impl BlockRule for ParagraphScanner {
-    fn check(_: &mut BlockState) -> Option<()> {
-        None // can't interrupt anything
+    fn can_interrupt(&self, _: InterruptTarget) -> bool {
+        false
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         let start_line = state.line;
         let mut next_line = start_line;
 
@@ -52,7 +54,7 @@ impl BlockRule for ParagraphScanner {
             // Some tags can terminate paragraph without empty line.
             let old_state_line = state.line;
             state.line = next_line;
-            if state.test_rules_at_line() {
+            if state.test_rules_at_line(InterruptTarget::Paragraph) {
                 state.line = old_state_line;
                 break 'outer;
             }
@@ -10,13 +10,17 @@
 use derivative::Derivative;
 use derive_more::{Deref, DerefMut};
 use downcast_rs::{impl_downcast, Downcast};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 
+use crate::common::sourcemap::SourcePos;
 use crate::common::utils::normalize_reference;
 use crate::generics::inline::full_link;
-use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
+use crate::parser::core::{CoreRule, Root};
+use crate::parser::diagnostics::{Diagnostic, Diagnostics};
 use crate::parser::extset::RootExt;
+use crate::parser::inline::builtin::InlineParserRule;
 use crate::{MarkdownIt, Node, NodeValue};
 
 /// Storage for parsed references
@@ -54,13 +58,13 @@ use crate::{MarkdownIt, Node, NodeValue};
 ///
 /// struct AddCustomReferences;
 /// impl CoreRule for AddCustomReferences {
-///     fn run(root: &mut Node, _: &MarkdownIt) {
+///     fn run(&self, root: &mut Node, _: &MarkdownIt) {
 ///         let data = root.cast_mut::<Root>().unwrap();
 ///         data.ext.insert(ReferenceMap::new(RefMapOverride::default()));
 ///     }
 /// }
 ///
-/// md.add_rule::<AddCustomReferences>()
+/// md.add_rule(AddCustomReferences)
 ///     .before::<BlockParserRule>();
 ///
 /// let html = md.parse("[rust]").render();
@@ -192,27 +196,34 @@ impl ReferenceMapEntry {
 
 /// Add plugin that parses markdown link references
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<ReferenceScanner>();
+    md.block.add_rule(ReferenceScanner);
+    md.add_rule(DefinitionBacklinks)
+        .after::<InlineParserRule>();
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Definition {
     pub label: String,
     pub destination: String,
     pub title: Option<String>,
+    /// Source positions of links and images that refer to this definition
+    /// (filled in after inline parsing, empty for duplicate definitions).
+    pub used_by: Vec<SourcePos>,
 }
 impl NodeValue for Definition {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, _: &mut dyn crate::Renderer) {}
 }
 
 #[doc(hidden)]
 pub struct ReferenceScanner;
 impl BlockRule for ReferenceScanner {
-    fn check(_: &mut BlockState) -> Option<()> {
-        None // can't interrupt anything
+    fn can_interrupt(&self, _: InterruptTarget) -> bool {
+        false
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
 
         if state.line_indent(state.line) >= state.md.max_indent { return None; }
 
@@ -256,7 +267,7 @@ impl BlockRule for ReferenceScanner {
             // Some tags can terminate paragraph without empty line.
             let old_state_line = state.line;
             state.line = next_line;
-            if state.test_rules_at_line() {
+            if state.test_rules_at_line(InterruptTarget::Reference) {
                 state.line = old_state_line;
                 break 'outer;
             }
@@ -342,7 +353,7 @@ impl BlockRule for ReferenceScanner {
         let mut chars = str[pos..].chars();
         loop {
             match chars.next() {
-                Some(' ' | '\t') => pos += 1,
+                Some(' ' | '\t') => {}
                 Some('\n') | None => break,
                 Some(_) if title.is_some() => {
                     // garbage at the end of the line after title,
@@ -363,12 +374,69 @@ impl BlockRule for ReferenceScanner {
         if !references.insert(str[1..label_end].to_owned(), href.clone(), title.clone()) { return None; }
 
         Some((Node::new(
-            Definition { 
-                label: str[1..label_end].to_owned(), 
-                destination: href, 
-                title
-            }), 
+            Definition {
+                label: str[1..label_end].to_owned(),
+                destination: href,
+                title,
+                used_by: Vec::new(),
+            }),
             lines + 1
         ))
     }
 }
+
+#[derive(Debug, Default, Clone)]
+/// Normalized reference label => positions of links that used it,
+/// filled in by [full_link] during inline parsing.
+pub(crate) struct ReferenceUsage(pub HashMap<String, Vec<SourcePos>>);
+impl RootExt for ReferenceUsage {
+    fn clone_ext(&self) -> Option<Box<dyn RootExt>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+impl ReferenceUsage {
+    pub fn add(&mut self, label: &str, srcmap: Option<SourcePos>) {
+        let Some(srcmap) = srcmap else { return; };
+        self.0.entry(normalize_reference(label)).or_default().push(srcmap);
+    }
+}
+
+#[doc(hidden)]
+pub struct DefinitionBacklinks;
+impl CoreRule for DefinitionBacklinks {
+    fn run(&self, root: &mut Node, _: &MarkdownIt) {
+        let data = root.cast_mut::<Root>().unwrap();
+        let mut usage = data.ext.remove::<ReferenceUsage>().unwrap_or_default();
+        let mut seen = HashSet::new();
+        let mut diagnostics = Vec::new();
+
+        root.walk_mut(|node, _| {
+            let Some(definition) = node.cast_mut::<Definition>() else { return; };
+            let label = normalize_reference(&definition.label);
+
+            // only the first definition with a given label is used, the rest are ignored
+            if !seen.insert(label.clone()) {
+                diagnostics.push(Diagnostic::warning(
+                    "duplicate-definition",
+                    format!("reference [{}] is already defined, this definition is ignored", definition.label),
+                    node.srcmap,
+                ));
+            } else if let Some(used_by) = usage.0.remove(&label) {
+                definition.used_by = used_by;
+            } else {
+                diagnostics.push(Diagnostic::warning(
+                    "unused-definition",
+                    format!("reference [{}] is never used", definition.label),
+                    node.srcmap,
+                ));
+            }
+        });
+
+        if !diagnostics.is_empty() {
+            let data = root.cast_mut::<Root>().unwrap();
+            let list = data.ext.get_or_insert_default::<Diagnostics>();
+            diagnostics.into_iter().for_each(|d| list.push(d));
+        }
+    }
+}
@@ -6,15 +6,17 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
-use crate::parser::inline::{InlineRule, InlineState, TextSpecial};
+use crate::parser::inline::{InlineRule, InlineState, TextSpecial, Trigger};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Autolink {
     pub url: String,
 }
 
 impl NodeValue for Autolink {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
         attrs.push(("href", self.url.clone()));
@@ -26,7 +28,7 @@ impl NodeValue for Autolink {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.inline.add_rule::<AutolinkScanner>();
+    md.inline.add_rule(AutolinkScanner);
 }
 
 static AUTOLINK_RE : Lazy<Regex> = Lazy::new(|| {
@@ -40,9 +42,9 @@ static EMAIL_RE : Lazy<Regex> = Lazy::new(|| {
 #[doc(hidden)]
 pub struct AutolinkScanner;
 impl InlineRule for AutolinkScanner {
-    const MARKER: char = '<';
+    fn trigger(&self) -> Trigger { Trigger::Char('<') }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '<' { return None; }
 
@@ -3,26 +3,120 @@
 //! `` `looks like this` ``
 //!
 //! <https://spec.commonmark.org/0.30/#code-span>
-use crate::generics::inline::code_pair;
+//!
+//! With [enable_lang_suffix], code span can be followed by `{:lang}` or `{.lang}`
+//! to set its language, and it gets highlighted by the current
+//! [highlighter](crate::parser::highlighter):
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::cmark::inline::backticks::enable_lang_suffix(md);
+//!
+//! let html = md.parse("`Vec<u8>`{:rust}").render();
+//! assert_eq!(html.trim(), r#"<p><code class="language-rust">Vec&lt;u8&gt;</code></p>"#);
+//! ```
+use std::sync::Arc;
+
+use crate::generics::inline::code_pair::{self, CodePairScanner};
+use crate::parser::extset::MarkdownItExt;
+use crate::parser::highlighter::{self, Highlighter};
+use crate::parser::inline::{InlineRule, InlineState, Text, Trigger};
+use crate::plugins::cmark::block::fence;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CodeInline {
     pub marker: char,
     pub marker_len: usize,
+    /// Language set with `{:lang}` suffix.
+    pub lang: Option<String>,
+    pub lang_prefix: &'static str,
+    /// Highlighter used to render this code span, if it has a language.
+    pub highlighter: Option<Arc<dyn Highlighter>>,
 }
 
 impl NodeValue for CodeInline {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
-        fmt.open("code", &node.attrs);
+        let mut attrs = node.attrs.clone();
+
+        if let Some(lang) = &self.lang {
+            attrs.push(("class", format!("{}{}", self.lang_prefix, lang)));
+
+            if let Some(highlighter) = &self.highlighter {
+                let code: String = node.children.iter()
+                    .filter_map(|child| child.cast::<Text>())
+                    .map(|text| text.content.as_str())
+                    .collect();
+
+                if let Some(html) = highlighter.highlight(Some(lang), &code) {
+                    attrs.extend(highlighter.inline_attrs());
+                    fmt.open("code", &attrs);
+                    fmt.text_raw(&html);
+                    fmt.close("code");
+                    return;
+                }
+            }
+        }
+
+        fmt.open("code", &attrs);
         fmt.contents(&node.children);
         fmt.close("code");
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+struct LangSuffix;
+impl MarkdownItExt for LangSuffix {}
+
 pub fn add(md: &mut MarkdownIt) {
-    code_pair::add_with::<'`'>(md, |len| Node::new(CodeInline {
-        marker: '`',
-        marker_len: len,
-    }));
+    md.inline.add_rule_with_id(code_pair::rule_id('`'), BacktickScanner(CodePairScanner::new('`', |len| {
+        Node::new(CodeInline {
+            marker: '`',
+            marker_len: len,
+            lang: None,
+            lang_prefix: "",
+            highlighter: None,
+        })
+    })));
+}
+
+/// Allow `{:lang}` or `{.lang}` right after code spans to set their language.
+pub fn enable_lang_suffix(md: &mut MarkdownIt) {
+    md.ext.insert(LangSuffix);
+}
+
+#[doc(hidden)]
+pub struct BacktickScanner(CodePairScanner);
+
+impl InlineRule for BacktickScanner {
+    fn trigger(&self) -> Trigger { self.0.trigger() }
+
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
+        let (mut node, mut len) = self.0.run(state)?;
+
+        if state.md.ext.get::<LangSuffix>().is_some() {
+            if let Some((lang, suffix_len)) = scan_lang_suffix(&state.src[state.pos + len..state.pos_max]) {
+                let code = node.cast_mut::<CodeInline>().unwrap();
+                code.lang = Some(lang.to_owned());
+                code.lang_prefix = fence::get_lang_prefix(state.md);
+                code.highlighter = highlighter::get_highlighter(state.md);
+                len += suffix_len;
+            }
+        }
+
+        Some((node, len))
+    }
+}
+
+/// Parse `{:lang}` or `{.lang}` at the start of the string, return language and length.
+fn scan_lang_suffix(str: &str) -> Option<(&str, usize)> {
+    let rest = str.strip_prefix("{:").or_else(|| str.strip_prefix("{."))?;
+    let end = rest.find('}')?;
+    let lang = &rest[..end];
+    let valid = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '+' | '#' | '.');
+    if lang.is_empty() || !lang.chars().all(valid) { return None; }
+    Some((lang, end + 3))
 }
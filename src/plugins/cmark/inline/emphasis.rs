@@ -6,12 +6,14 @@
 use crate::generics::inline::emph_pair;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Em {
     pub marker: char
 }
 
 impl NodeValue for Em {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.open("em", &node.attrs);
         fmt.contents(&node.children);
@@ -19,12 +21,14 @@ impl NodeValue for Em {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Strong {
     pub marker: char
 }
 
 impl NodeValue for Strong {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.open("strong", &node.attrs);
         fmt.contents(&node.children);
@@ -33,8 +37,8 @@ impl NodeValue for Strong {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    emph_pair::add_with::<'*', 1, true>  (md, || Node::new(Em     { marker: '*' }));
-    emph_pair::add_with::<'_', 1, false> (md, || Node::new(Em     { marker: '_' }));
-    emph_pair::add_with::<'*', 2, true>  (md, || Node::new(Strong { marker: '*' }));
-    emph_pair::add_with::<'_', 2, false> (md, || Node::new(Strong { marker: '_' }));
+    emph_pair::add_with(md, '*', 1, true,  || Node::new(Em     { marker: '*' }));
+    emph_pair::add_with(md, '_', 1, false, || Node::new(Em     { marker: '_' }));
+    emph_pair::add_with(md, '*', 2, true,  || Node::new(Strong { marker: '*' }));
+    emph_pair::add_with(md, '_', 2, false, || Node::new(Strong { marker: '_' }));
 }
@@ -7,11 +7,11 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::common::utils::{get_entity_from_str, is_valid_entity_code};
-use crate::parser::inline::{InlineRule, InlineState, TextSpecial};
+use crate::parser::inline::{InlineRule, InlineState, TextSpecial, Trigger};
 use crate::{MarkdownIt, Node};
 
 pub fn add(md: &mut MarkdownIt) {
-    md.inline.add_rule::<EntityScanner>();
+    md.inline.add_rule(EntityScanner);
 }
 
 static DIGITAL_RE : Lazy<Regex> = Lazy::new(|| {
@@ -70,9 +70,9 @@ impl EntityScanner {
 }
 
 impl InlineRule for EntityScanner {
-    const MARKER: char = '&';
+    fn trigger(&self) -> Trigger { Trigger::Char('&') }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '&' { return None; }
 
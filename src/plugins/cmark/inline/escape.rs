@@ -4,20 +4,20 @@
 //! of the line.
 //!
 //! <https://spec.commonmark.org/0.30/#backslash-escapes>
-use crate::parser::inline::{InlineRule, InlineState, TextSpecial};
+use crate::parser::inline::{InlineRule, InlineState, TextSpecial, Trigger};
 use crate::plugins::cmark::inline::newline::Hardbreak;
 use crate::{MarkdownIt, Node};
 
 pub fn add(md: &mut MarkdownIt) {
-    md.inline.add_rule::<EscapeScanner>();
+    md.inline.add_rule(EscapeScanner);
 }
 
 #[doc(hidden)]
 pub struct EscapeScanner;
 impl InlineRule for EscapeScanner {
-    const MARKER: char = '\\';
+    fn trigger(&self) -> Trigger { Trigger::Char('\\') }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '\\' { return None; }
 
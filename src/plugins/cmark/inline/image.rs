@@ -3,16 +3,22 @@
 //! `![image](<src> "title")`
 //!
 //! <https://spec.commonmark.org/0.30/#images>
-use crate::generics::inline::full_link;
+use crate::generics::inline::full_link::{self, LinkKind};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Image {
     pub url: String,
     pub title: Option<String>,
+    /// Syntax form this image is written in.
+    pub kind: LinkKind,
+    /// Reference label as written in the source (for reference images only).
+    pub reference: Option<String>,
 }
 
 impl NodeValue for Image {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
         attrs.push(("src", self.url.clone()));
@@ -27,8 +33,10 @@ impl NodeValue for Image {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    full_link::add_prefix::<'!', true>(md, |href, title| Node::new(Image {
-        url: href.unwrap_or_default(),
-        title,
+    full_link::add_prefix(md, '!', true, |ctx| Node::new(Image {
+        url: ctx.href.unwrap_or_default(),
+        title: ctx.title,
+        kind: ctx.kind,
+        reference: ctx.reference,
     }));
 }
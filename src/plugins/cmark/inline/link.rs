@@ -3,16 +3,23 @@
 //! `![link](<to> "stuff")`
 //!
 //! <https://spec.commonmark.org/0.30/#links>
-use crate::generics::inline::full_link;
+use crate::generics::inline::full_link::{self, LinkKind};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Link {
     pub url: String,
     pub title: Option<String>,
+    /// Syntax form this link is written in (autolinks like `<https://example.org>`
+    /// produce [Autolink](super::autolink::Autolink) nodes instead).
+    pub kind: LinkKind,
+    /// Reference label as written in the source (for reference links only).
+    pub reference: Option<String>,
 }
 
 impl NodeValue for Link {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
         attrs.push(("href", self.url.clone()));
@@ -28,8 +35,10 @@ impl NodeValue for Link {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    full_link::add::<false>(md, |href, title| Node::new(Link {
-        url: href.unwrap_or_default(),
-        title,
+    full_link::add(md, false, |ctx| Node::new(Link {
+        url: ctx.href.unwrap_or_default(),
+        title: ctx.title,
+        kind: ctx.kind,
+        reference: ctx.reference,
     }));
 }
@@ -4,45 +4,49 @@
 //!
 //!  - <https://spec.commonmark.org/0.30/#hard-line-breaks>
 //!  - <https://spec.commonmark.org/0.30/#soft-line-breaks>
-use crate::parser::inline::{InlineRule, InlineState};
+use crate::parser::inline::{InlineRule, InlineState, Trigger};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Hardbreak;
 
 impl NodeValue for Hardbreak {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.self_close("br", &[]);
         fmt.cr();
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Softbreak;
 
 impl NodeValue for Softbreak {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
     }
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.inline.add_rule::<NewlineScanner>();
+    md.inline.add_rule(NewlineScanner);
 }
 
 #[doc(hidden)]
 pub struct NewlineScanner;
 impl InlineRule for NewlineScanner {
-    const MARKER: char = '\n';
+    fn trigger(&self) -> Trigger { Trigger::Char('\n') }
 
-    fn check(state: &mut InlineState) -> Option<usize> {
+    fn check(&self, state: &mut InlineState) -> Option<usize> {
         // check rule is required because run() modifies trailing text
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '\n' { return None; }
         Some(1)
     }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
 
         if chars.next().unwrap() != '\n' { return None; }
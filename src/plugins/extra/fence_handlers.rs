@@ -0,0 +1,246 @@
+//! Code fences with special meaning, turned into other nodes by language name.
+//!
+//!  - `mermaid` (or any other diagram language) - `<pre class="mermaid">` for client-side rendering
+//!  - `csv` - table
+//!  - `math` - display math
+//!  - `markdown` - rendered markdown followed by its source
+//!
+//! These are built with [fence::add_handler], so they are never highlighted.
+//!
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::extra::fence_handlers::add(md);
+//!
+//! let html = md.parse("```mermaid\ngraph TD; A-->B;\n```").render();
+//! assert_eq!(html, "<pre class=\"mermaid\">graph TD; A--&gt;B;\n</pre>\n");
+//!
+//! let html = md.parse("```csv\nname,value\nfoo,\"1,5\"\n```").render();
+//! assert!(html.contains("<th>name</th>"));
+//! assert!(html.contains("<td>1,5</td>"));
+//! ```
+use crate::parser::diagnostics::Diagnostics;
+use crate::parser::inline::builtin::parse_inline_roots;
+use crate::parser::inline::Text;
+use crate::plugins::cmark::block::fence;
+use crate::plugins::cmark::block::reference::Definition;
+use crate::plugins::extra::tables::{ColumnAlignment, Table, TableBody, TableCell, TableHead, TableRow};
+use crate::{MarkdownIt, Node, NodeValue, Renderer};
+
+#[derive(Debug, Clone)]
+/// Diagram source to be rendered on the client side (e.g. by mermaid.js).
+pub struct Diagram {
+    pub lang: String,
+    pub content: String,
+}
+
+impl NodeValue for Diagram {
+    crate::impl_clone_value!();
+
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class", self.lang.clone()));
+
+        fmt.cr();
+        fmt.open("pre", &attrs);
+        fmt.text(&self.content);
+        fmt.close("pre");
+        fmt.cr();
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Block of TeX math, rendered as `\[...\]` for client-side rendering (e.g. by KaTeX).
+pub struct DisplayMath {
+    pub content: String,
+}
+
+impl NodeValue for DisplayMath {
+    crate::impl_clone_value!();
+
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class", "math display".into()));
+
+        fmt.cr();
+        fmt.open("div", &attrs);
+        fmt.text("\\[");
+        fmt.text(self.content.trim_end());
+        fmt.text("\\]");
+        fmt.close("div");
+        fmt.cr();
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Markdown example, rendered result is stored in children.
+pub struct MarkdownPreview {
+    pub source: String,
+}
+
+impl NodeValue for MarkdownPreview {
+    crate::impl_clone_value!();
+
+    fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+        let mut attrs = node.attrs.clone();
+        attrs.push(("class", "markdown-preview".into()));
+
+        fmt.cr();
+        fmt.open("div", &attrs);
+        fmt.cr();
+        fmt.open("div", &[("class", "preview".into())]);
+        fmt.cr();
+        fmt.contents(&node.children);
+        fmt.cr();
+        fmt.close("div");
+        fmt.cr();
+        fmt.open("pre", &[]);
+        fmt.open("code", &[("class", "language-markdown".into())]);
+        fmt.text(&self.source);
+        fmt.close("code");
+        fmt.close("pre");
+        fmt.cr();
+        fmt.close("div");
+        fmt.cr();
+    }
+}
+
+/// Add all handlers from this module (`mermaid`, `csv`, `math` and `markdown`).
+pub fn add(md: &mut MarkdownIt) {
+    add_diagram(md, "mermaid");
+    add_csv(md);
+    add_math(md);
+    add_markdown_preview(md);
+}
+
+/// Turn fences with language `lang` into [Diagram] nodes.
+pub fn add_diagram(md: &mut MarkdownIt, lang: &str) {
+    let lang = lang.to_owned();
+    fence::add_handler(md, lang.clone(), move |fence, _| {
+        Some(Node::new(Diagram { lang: lang.clone(), content: fence.content.clone() }))
+    });
+}
+
+/// Turn `csv` fences into tables, first line is a header.
+pub fn add_csv(md: &mut MarkdownIt) {
+    fence::add_handler(md, "csv", |fence, _| {
+        let mut rows = parse_csv(&fence.content).into_iter();
+        let head = rows.next()?;
+
+        let mut table = Node::new(Table { alignments: vec![ColumnAlignment::None; head.len()] });
+        let mut thead = Node::new(TableHead);
+        thead.children.push(make_row(head));
+        table.children.push(thead);
+
+        let body: Vec<_> = rows.map(make_row).collect();
+        if !body.is_empty() {
+            let mut tbody = Node::new(TableBody);
+            tbody.children = body;
+            table.children.push(tbody);
+        }
+
+        Some(table)
+    });
+}
+
+/// Turn `math` fences into [DisplayMath] nodes.
+pub fn add_math(md: &mut MarkdownIt) {
+    fence::add_handler(md, "math", |fence, _| {
+        Some(Node::new(DisplayMath { content: fence.content.clone() }))
+    });
+}
+
+/// Turn `markdown` fences into [MarkdownPreview] nodes.
+///
+/// Preview is parsed as a separate document: reference definitions inside of it
+/// only apply to links inside of it, and don't affect the rest of the document.
+pub fn add_markdown_preview(md: &mut MarkdownIt) {
+    fence::add_handler(md, "markdown", |fence, state| {
+        // parse content in place, so source positions are shared with the rest
+        // of the document, but with its own extension set (and reference map)
+        let node = Node::new(MarkdownPreview { source: fence.content.clone() });
+        let old_node = std::mem::replace(&mut state.node, node);
+        let old_ext = std::mem::take(state.root_ext);
+        state.md.block.tokenize(state);
+        let mut node = std::mem::replace(&mut state.node, old_node);
+        let mut ext = std::mem::replace(state.root_ext, old_ext);
+
+        // inline content is parsed right away, because references are resolved then
+        parse_inline_roots(&mut node, state.md, &mut ext);
+
+        // definitions only apply to links in the preview, which are resolved by now;
+        // they are removed so that they aren't checked against links of the outer document
+        node.walk_mut(|node, _| node.children.retain(|child| !child.is::<Definition>()));
+
+        if let Some(diagnostics) = ext.get::<Diagnostics>() {
+            let outer = state.root_ext.get_or_insert_default::<Diagnostics>();
+            diagnostics.iter().for_each(|d| outer.push(d.clone()));
+        }
+
+        Some(node)
+    });
+}
+
+fn make_row(cells: Vec<String>) -> Node {
+    let mut row = Node::new(TableRow);
+    for content in cells {
+        let mut cell = Node::new(TableCell);
+        if !content.is_empty() {
+            cell.children.push(Node::new(Text { content }));
+        }
+        row.children.push(cell);
+    }
+    row
+}
+
+/// Split comma-separated values into rows and cells (RFC 4180, blank lines are skipped).
+fn parse_csv(source: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = source.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if cell.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                if !row.is_empty() || !cell.is_empty() {
+                    row.push(std::mem::take(&mut cell));
+                    rows.push(std::mem::take(&mut row));
+                }
+            }
+            _ => cell.push(ch),
+        }
+    }
+
+    if !row.is_empty() || !cell.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_csv;
+
+    #[test]
+    fn csv_quotes_and_blank_lines() {
+        assert_eq!(parse_csv("a,\"b,\"\"c\"\"\"\r\n\n\"multi\nline\",\n"), [
+            vec!["a", "b,\"c\""],
+            vec!["multi\nline", ""],
+        ]);
+    }
+}
@@ -24,7 +24,7 @@ use crate::{MarkdownIt, Node};
 
 pub fn add(md: &mut MarkdownIt, slugify: fn (&str) -> String) {
     md.ext.insert(SlugifyFunction(slugify));
-    md.add_rule::<AddHeadingAnchors>();
+    md.add_rule(AddHeadingAnchors);
 }
 
 /// Simple built-in slugify function. It is added for testing and demonstration
@@ -57,7 +57,7 @@ impl Debug for SlugifyFunction {
 
 pub struct AddHeadingAnchors;
 impl CoreRule for AddHeadingAnchors {
-    fn run(root: &mut Node, md: &MarkdownIt) {
+    fn run(&self, root: &mut Node, md: &MarkdownIt) {
         let slugify = md.ext.get::<SlugifyFunction>().copied().unwrap_or_default().0;
 
         root.walk_mut(|node, _| {
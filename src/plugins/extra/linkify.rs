@@ -8,19 +8,21 @@ use std::cmp::Ordering;
 use crate::parser::core::{CoreRule, Root};
 use crate::parser::extset::RootExt;
 use crate::parser::inline::builtin::InlineParserRule;
-use crate::parser::inline::{InlineRule, InlineState, TextSpecial};
+use crate::parser::inline::{InlineRule, InlineState, TextSpecial, Trigger};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
 static SCHEME_RE : Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(?i)(?:^|[^a-z0-9.+-])([a-z][a-z0-9.+-]*)$").unwrap()
 });
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Linkified {
     pub url: String,
 }
 
 impl NodeValue for Linkified {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let mut attrs = node.attrs.clone();
         attrs.push(("href", self.url.clone()));
@@ -32,14 +34,18 @@ impl NodeValue for Linkified {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.add_rule::<LinkifyPrescan>()
+    md.add_rule(LinkifyPrescan)
         .before::<InlineParserRule>();
 
-    md.inline.add_rule::<LinkifyScanner>();
+    md.inline.add_rule(LinkifyScanner);
 }
 
 type LinkifyState = Vec<LinkifyPosition>;
-impl RootExt for LinkifyState {}
+impl RootExt for LinkifyState {
+    fn clone_ext(&self) -> Option<Box<dyn RootExt>> {
+        Some(Box::new(self.clone()))
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 struct LinkifyPosition {
@@ -51,7 +57,7 @@ struct LinkifyPosition {
 #[doc(hidden)]
 pub struct LinkifyPrescan;
 impl CoreRule for LinkifyPrescan {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(&self, root: &mut Node, _: &MarkdownIt) {
         let root_data = root.cast_mut::<Root>().unwrap();
         let source = root_data.content.as_str();
         let finder = LinkFinder::new();
@@ -73,9 +79,9 @@ impl CoreRule for LinkifyPrescan {
 #[doc(hidden)]
 pub struct LinkifyScanner;
 impl InlineRule for LinkifyScanner {
-    const MARKER: char = ':';
+    fn trigger(&self) -> Trigger { Trigger::Char(':') }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != ':' { return None; }
         if state.link_level > 0 { return None; }
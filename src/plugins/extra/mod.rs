@@ -6,6 +6,7 @@
 //!  - beautify links (cut "http://" from links and shorten paths)
 //!  - smartquotes and typographer
 //!  - code block highlighting using `syntect`
+//!  - fences turned into diagrams, tables, math and markdown previews (not added by default)
 //!
 //! ```rust
 //! let md = &mut markdown_it::MarkdownIt::new();
@@ -19,6 +20,7 @@
 //! assert_eq!(html.trim(), r#"<p>Markdown done “The Right Way™”</p>"#);
 //! ```
 pub mod beautify_links;
+pub mod fence_handlers;
 pub mod heading_anchors;
 #[cfg(feature = "linkify")]
 pub mod linkify;
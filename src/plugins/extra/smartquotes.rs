@@ -1,8 +1,7 @@
 //! Replaces `"` and `'` quotes with "nicer" ones like `‘`, `’`, `“`, `”`, or
 //! with `’` for words like "isn't".
 //!
-//! This currently only supports single character quotes, which is a limitation
-//! of the Rust implementation due to the use of `const` generics.
+//! This currently only supports single character quotes.
 //!
 //! ## Implementation notes
 //!
@@ -41,22 +40,23 @@ const SPACE: char = ' ';
 
 /// Add smartquotes with the "classic" quote set of `‘`, `’`, `“`, and `”`.
 pub fn add(md: &mut MarkdownIt) {
-    add_with::<'‘', '’', '“', '”'>(md);
+    add_with(md, '‘', '’', '“', '”');
 }
 
-pub fn add_with<
-    const OPEN_SINGLE_QUOTE: char,
-    const CLOSE_SINGLE_QUOTE: char,
-    const OPEN_DOUBLE_QUOTE: char,
-    const CLOSE_DOUBLE_QUOTE: char,
->(
+/// Add smartquotes with a custom quote set.
+pub fn add_with(
     md: &mut MarkdownIt,
+    open_single_quote: char,
+    close_single_quote: char,
+    open_double_quote: char,
+    close_double_quote: char,
 ) {
-    md.add_rule::<SmartQuotesRule<
-        OPEN_SINGLE_QUOTE,
-        CLOSE_SINGLE_QUOTE,
-        OPEN_DOUBLE_QUOTE,
-        CLOSE_DOUBLE_QUOTE>>();
+    md.add_rule(SmartQuotesRule {
+        open_single_quote,
+        close_single_quote,
+        open_double_quote,
+        close_double_quote,
+    });
 }
 
 /// Simplified Node type that only holds the info we need
@@ -123,30 +123,18 @@ struct ReplacementOp {
     quote: char,
 }
 
-pub struct SmartQuotesRule<
-    const OPEN_SINGLE_QUOTE: char,
-    const CLOSE_SINGLE_QUOTE: char,
-    const OPEN_DOUBLE_QUOTE: char,
-    const CLOSE_DOUBLE_QUOTE: char,
->;
-
-impl<
-        const OPEN_SINGLE_QUOTE: char,
-        const CLOSE_SINGLE_QUOTE: char,
-        const OPEN_DOUBLE_QUOTE: char,
-        const CLOSE_DOUBLE_QUOTE: char,
-    > CoreRule
-    for SmartQuotesRule<
-        OPEN_SINGLE_QUOTE,
-        CLOSE_SINGLE_QUOTE,
-        OPEN_DOUBLE_QUOTE,
-        CLOSE_DOUBLE_QUOTE,
-    >
-{
-    fn run(root: &mut Node, _: &MarkdownIt) {
+pub struct SmartQuotesRule {
+    pub open_single_quote: char,
+    pub close_single_quote: char,
+    pub open_double_quote: char,
+    pub close_double_quote: char,
+}
+
+impl CoreRule for SmartQuotesRule {
+    fn run(&self, root: &mut Node, _: &MarkdownIt) {
         let text_tokens = all_text_tokens(root);
 
-        let replacement_ops = Self::compute_replacements(text_tokens);
+        let replacement_ops = self.compute_replacements(text_tokens);
 
         // now that we know what we want to replace where, we go over the nodes a _third_ time to do all the actual replacements.
         let mut current_index: usize = 0;
@@ -162,20 +150,13 @@ impl<
     }
 }
 
-impl<
-        const OPEN_SINGLE_QUOTE: char,
-        const CLOSE_SINGLE_QUOTE: char,
-        const OPEN_DOUBLE_QUOTE: char,
-        const CLOSE_DOUBLE_QUOTE: char,
-    >
-    SmartQuotesRule<OPEN_SINGLE_QUOTE, CLOSE_SINGLE_QUOTE, OPEN_DOUBLE_QUOTE, CLOSE_DOUBLE_QUOTE>
-{
+impl SmartQuotesRule {
     /// Walk the list of tokens to figure out what needs replacing where. to do
     /// this, we need to search back and forth over the nodes to find matching
     /// quotes across nodes. The borrow checker won't let us handle the entire
     /// set of nodes as mutable at the same time however, so all we do here is
     /// figure out what we _want_ to replace in which node.
-    fn compute_replacements(text_tokens: Vec<FlatToken>) -> HashMap<usize, HashMap<usize, char>> {
+    fn compute_replacements(&self, text_tokens: Vec<FlatToken>) -> HashMap<usize, HashMap<usize, char>> {
         let mut quote_stack: Vec<QuoteMarker> = Vec::new();
         let mut replacement_ops: HashMap<usize, HashMap<usize, char>> = HashMap::new();
         for (walk_index, token) in text_tokens.iter().enumerate() {
@@ -184,7 +165,7 @@ impl<
                 nesting_level,
             } = token
             {
-                for op in Self::replace_smartquotes(
+                for op in self.replace_smartquotes(
                     content,
                     walk_index,
                     *nesting_level,
@@ -203,6 +184,7 @@ impl<
 
     /// Compute quote replacements found by looking at a single text block
     fn replace_smartquotes(
+        &self,
         content: &str,
         walk_index: usize,
         level: u32,
@@ -236,7 +218,7 @@ impl<
 
             if can_close {
                 if let Some((opening_op, closing_op, new_stack_len)) =
-                    Self::try_close(quote_stack, walk_index, level, quote_type, quote_position)
+                    self.try_close(quote_stack, walk_index, level, quote_type, quote_position)
                 {
                     quote_stack.truncate(new_stack_len);
                     result.push(opening_op);
@@ -268,6 +250,7 @@ impl<
     /// If a match is found, returns `Some` with two `ReplacementOp`s to be
     /// added to the result, and with the resulting length of the `quote_stack`.
     fn try_close(
+        &self,
         quote_stack: &[QuoteMarker],
         walk_index: usize,
         level: u32,
@@ -284,18 +267,18 @@ impl<
                         walk_index: other_item.walk_index,
                         quote_position: other_item.quote_position,
                         quote: if quote_type == QuoteType::Single {
-                            OPEN_SINGLE_QUOTE
+                            self.open_single_quote
                         } else {
-                            OPEN_DOUBLE_QUOTE
+                            self.open_double_quote
                         },
                     },
                     ReplacementOp {
                         walk_index,
                         quote_position,
                         quote: if quote_type == QuoteType::Single {
-                            CLOSE_SINGLE_QUOTE
+                            self.close_single_quote
                         } else {
-                            CLOSE_DOUBLE_QUOTE
+                            self.close_double_quote
                         },
                     },
                     j,
@@ -320,7 +303,7 @@ impl<
 /// Every entry in the `Vec` will produce an entry in the result, meaning that
 /// the index of a token in the resulting `Vec` will be the same as the index it
 /// would get during a `root.walk` call.
-fn all_text_tokens(root: &Node) -> Vec<FlatToken> {
+fn all_text_tokens(root: &Node) -> Vec<FlatToken<'_>> {
     let mut result = Vec::new();
     let mut walk_index = 0;
     root.walk(|node, nesting_level| {
@@ -2,12 +2,14 @@
 use crate::generics::inline::emph_pair;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Strikethrough {
     pub marker: char
 }
 
 impl NodeValue for Strikethrough {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.open("s", &node.attrs);
         fmt.contents(&node.children);
@@ -16,5 +18,5 @@ impl NodeValue for Strikethrough {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    emph_pair::add_with::<'~', 2, true>(md, || Node::new(Strikethrough { marker: '~' }));
+    emph_pair::add_with(md, '~', 2, true, || Node::new(Strikethrough { marker: '~' }));
 }
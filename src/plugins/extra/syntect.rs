@@ -1,74 +1,244 @@
 //! Syntax highlighting for code blocks
-use syntect::highlighting::ThemeSet;
-use syntect::html::highlighted_html_for_string;
-use syntect::parsing::SyntaxSet;
+//!
+//! This plugin sets [SyntectHighlighter] as a [highlighter](crate::parser::highlighter)
+//! for code blocks and fences, syntax tree is not modified.
+//!
+//! Default syntax definitions and themes are loaded once and shared between all
+//! parser instances. You can use your own theme or add syntax definitions:
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::syntect;
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! syntect::add(md);
+//! syntect::set_theme(md, "base16-ocean.dark");
+//!
+//! let html = md.parse("```rust\nlet x = 1;\n```").render();
+//! assert!(html.starts_with(r#"<pre style="background-color:#2b303b;">"#));
+//! ```
+//!
+//! By default colors are written into `style` attributes. If you can't use inline
+//! styles (e.g. because of Content Security Policy) or want to switch themes
+//! with css, syntect can mark up code with classes instead, and you can generate
+//! a matching stylesheet with [css_for_theme] or [css_for_light_dark]:
+//!
+//! ```rust
+//! use markdown_it::plugins::extra::syntect::{self, ClassStyle, HighlightOutput};
+//!
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! syntect::add(md);
+//! syntect::set_output(md, HighlightOutput::Classes(ClassStyle::Spaced));
+//!
+//! let html = md.parse("```rust\nlet x = 1;\n```").render();
+//! assert!(html.starts_with(r#"<pre class="code"><code class="language-rust"><span class="source rust">"#));
+//!
+//! let theme = syntect::builtin_theme("base16-ocean.dark").unwrap();
+//! let css = syntect::css_for_theme(theme, ClassStyle::Spaced);
+//! assert!(css.contains(".code {\n color: #c0c5ce;\n background-color: #2b303b;\n}"));
+//! ```
+use derivative::Derivative;
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::Arc;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, Theme, ThemeSet};
+use syntect::html::{
+    append_highlighted_html_for_styled_line, css_for_theme_with_class_style,
+    ClassedHTMLGenerator, IncludeBackground,
+};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+use syntect::LoadingError;
+
+pub use syntect::html::ClassStyle;
 
-use crate::parser::core::CoreRule;
 use crate::parser::extset::MarkdownItExt;
-use crate::plugins::cmark::block::code::CodeBlock;
-use crate::plugins::cmark::block::fence::CodeFence;
-use crate::{MarkdownIt, Node, NodeValue, Renderer};
+use crate::parser::highlighter::{get_highlighter, set_highlighter, Highlighter};
+use crate::MarkdownIt;
+
+static DEFAULT_SYNTAXES : Lazy<Arc<SyntaxSet>> = Lazy::new(|| Arc::new(SyntaxSet::load_defaults_newlines()));
+static DEFAULT_THEMES : Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+static DEFAULT_THEME : Lazy<Arc<Theme>> = Lazy::new(|| Arc::new(DEFAULT_THEMES.themes["InspiredGitHub"].clone()));
 
-#[derive(Debug)]
-pub struct SyntectSnippet {
-    pub html: String,
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+/// How highlighted code is styled.
+pub enum HighlightOutput {
+    /// Colors of the current theme are written into `style` attributes.
+    #[default]
+    InlineStyles,
+    /// Code is marked up with classes named after syntax scopes, theme is applied
+    /// with a stylesheet generated by [css_for_theme].
+    Classes(ClassStyle),
 }
 
-impl NodeValue for SyntectSnippet {
-    fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
-        fmt.text_raw(&self.html);
-    }
+#[derive(Derivative, Clone)]
+#[derivative(Debug)]
+/// Highlighter using syntect, configured with functions in this module.
+pub struct SyntectHighlighter {
+    // syntax set and theme are large, don't dump them into every code node's debug output
+    #[derivative(Debug="ignore")]
+    syntaxes: Arc<SyntaxSet>,
+    #[derivative(Debug="ignore")]
+    theme: Arc<Theme>,
+    output: HighlightOutput,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct SyntectSettings(&'static str);
-impl MarkdownItExt for SyntectSettings {}
+// current configuration, also kept if highlighter isn't added yet
+impl MarkdownItExt for SyntectHighlighter {}
 
-impl Default for SyntectSettings {
+impl Default for SyntectHighlighter {
     fn default() -> Self {
-        Self("InspiredGitHub")
+        Self {
+            syntaxes: DEFAULT_SYNTAXES.clone(),
+            theme: DEFAULT_THEME.clone(),
+            output: HighlightOutput::default(),
+        }
+    }
+}
+
+impl Highlighter for SyntectHighlighter {
+    fn highlight(&self, lang: Option<&str>, code: &str) -> Option<String> {
+        let ss = self.syntaxes.as_ref();
+        let syntax = lang.and_then(|lang| ss.find_syntax_by_token(lang));
+        let syntax = syntax.unwrap_or_else(|| ss.find_syntax_plain_text());
+        self.highlight_with(syntax, code).ok()
+    }
+
+    fn pre_attrs(&self) -> Vec<(&'static str, String)> {
+        match self.output {
+            HighlightOutput::InlineStyles => {
+                let bg = self.background();
+                vec![("style", format!("background-color:#{:02x}{:02x}{:02x};", bg.r, bg.g, bg.b))]
+            }
+            HighlightOutput::Classes(ClassStyle::SpacedPrefixed { prefix }) => {
+                vec![("class", format!("{prefix}code"))]
+            }
+            HighlightOutput::Classes(_) => {
+                vec![("class", "code".to_owned())]
+            }
+        }
+    }
+
+    fn inline_attrs(&self) -> Vec<(&'static str, String)> {
+        match self.output {
+            // colors of the theme may not be readable on page background
+            HighlightOutput::InlineStyles => self.pre_attrs(),
+            HighlightOutput::Classes(_) => Vec::new(),
+        }
+    }
+}
+
+impl SyntectHighlighter {
+    fn background(&self) -> Color {
+        self.theme.settings.background.unwrap_or(Color::WHITE)
+    }
+
+    fn highlight_with(&self, syntax: &SyntaxReference, code: &str) -> Result<String, syntect::Error> {
+        let ss = self.syntaxes.as_ref();
+
+        match self.output {
+            HighlightOutput::InlineStyles => {
+                let bg = IncludeBackground::IfDifferent(self.background());
+                let mut highlighter = HighlightLines::new(syntax, &self.theme);
+                let mut html = String::new();
+                for line in LinesWithEndings::from(code) {
+                    let regions = highlighter.highlight_line(line, ss)?;
+                    append_highlighted_html_for_styled_line(&regions, bg, &mut html)?;
+                }
+                Ok(html)
+            }
+            HighlightOutput::Classes(style) => {
+                let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, ss, style);
+                for line in LinesWithEndings::from(code) {
+                    generator.parse_html_for_line_which_includes_newline(line)?;
+                }
+                Ok(generator.finalize())
+            }
+        }
     }
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.add_rule::<SyntectRule>();
+    let highlighter = md.ext.get_or_insert_default::<SyntectHighlighter>().clone();
+    set_highlighter(md, highlighter);
 }
 
-pub fn set_theme(md: &mut MarkdownIt, theme: &'static str) {
-    md.ext.insert(SyntectSettings(theme));
+/// Change configuration, and update the highlighter if it's already added.
+fn configure(md: &mut MarkdownIt, f: impl FnOnce(&mut SyntectHighlighter)) {
+    let settings = md.ext.get_or_insert_default::<SyntectHighlighter>();
+    f(settings);
+    let settings = settings.clone();
+    if get_highlighter(md).is_some_and(|h| h.is::<SyntectHighlighter>()) {
+        set_highlighter(md, settings);
+    }
 }
 
-pub struct SyntectRule;
-impl CoreRule for SyntectRule {
-    fn run(root: &mut Node, md: &MarkdownIt) {
-        let ss = SyntaxSet::load_defaults_newlines();
-        let ts = ThemeSet::load_defaults();
-        let theme = &ts.themes[md.ext.get::<SyntectSettings>().copied().unwrap_or_default().0];
+/// Use one of the themes bundled with syntect (e.g. `base16-ocean.dark`),
+/// see [builtin_themes] for the full list.
+///
+/// # Panics
+///
+/// Panics if there's no built-in theme with that name.
+pub fn set_theme(md: &mut MarkdownIt, theme: &str) {
+    let Some(theme) = DEFAULT_THEMES.themes.get(theme) else {
+        panic!("unknown syntect theme: {theme}");
+    };
+    set_custom_theme(md, theme.clone());
+}
 
-        root.walk_mut(|node, _| {
-            let mut content = None;
-            let mut language = None;
+/// Use your own theme, e.g. loaded with [ThemeSet::get_theme] from a `.tmTheme` file.
+pub fn set_custom_theme(md: &mut MarkdownIt, theme: Theme) {
+    configure(md, |settings| settings.theme = Arc::new(theme));
+}
 
-            if let Some(data) = node.cast::<CodeBlock>() {
-                content = Some(&data.content);
-            } else if let Some(data) = node.cast::<CodeFence>() {
-                language = Some(data.info.clone());
-                content = Some(&data.content);
-            }
+/// Use your own set of syntax definitions instead of the default ones.
+pub fn set_syntaxes(md: &mut MarkdownIt, syntaxes: SyntaxSet) {
+    configure(md, |settings| settings.syntaxes = Arc::new(syntaxes));
+}
 
-            if let Some(content) = content {
-                let mut syntax = None;
-                if let Some(language) = language {
-                    syntax = ss.find_syntax_by_token(&language);
-                }
-                let syntax = syntax.unwrap_or_else(|| ss.find_syntax_plain_text());
+/// Add syntax definitions (`.sublime-syntax` files) from a local folder
+/// to the ones currently used.
+pub fn add_syntaxes_from_folder(md: &mut MarkdownIt, folder: impl AsRef<Path>) -> Result<(), LoadingError> {
+    let settings = md.ext.get_or_insert_default::<SyntectHighlighter>();
+    let mut builder = settings.syntaxes.as_ref().clone().into_builder();
+    builder.add_from_folder(folder, true)?;
+    set_syntaxes(md, builder.build());
+    Ok(())
+}
 
-                let html = highlighted_html_for_string(content, &ss, syntax, theme);
+/// Choose between inline styles (default) and class-based output.
+pub fn set_output(md: &mut MarkdownIt, output: HighlightOutput) {
+    configure(md, |settings| settings.output = output);
+}
 
-                if let Ok(html) = html {
-                    node.replace(SyntectSnippet { html });
-                }
-            }
-        });
+/// Names of the themes bundled with syntect.
+pub fn builtin_themes() -> impl Iterator<Item = &'static str> {
+    DEFAULT_THEMES.themes.keys().map(|name| name.as_str())
+}
+
+/// Get one of the themes bundled with syntect by name.
+pub fn builtin_theme(name: &str) -> Option<&'static Theme> {
+    DEFAULT_THEMES.themes.get(name)
+}
+
+/// Generate a stylesheet for [HighlightOutput::Classes] output with a given theme,
+/// `style` must be the same as the one used for highlighting.
+pub fn css_for_theme(theme: &Theme, style: ClassStyle) -> String {
+    css_for_theme_with_class_style(theme, style).expect("writing to a string can't fail")
+}
+
+/// Generate a stylesheet which uses `light` theme by default and `dark` theme
+/// if user prefers dark color scheme.
+pub fn css_for_light_dark(light: &Theme, dark: &Theme, style: ClassStyle) -> String {
+    let mut css = css_for_theme(light, style);
+    css.push_str("@media (prefers-color-scheme: dark) {\n");
+    for line in css_for_theme(dark, style).lines() {
+        if !line.is_empty() { css.push_str("  "); }
+        css.push_str(line);
+        css.push('\n');
     }
+    css.push_str("}\n");
+    css
 }
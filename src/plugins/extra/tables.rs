@@ -2,19 +2,21 @@
 //!
 //! <https://github.github.com/gfm/#tables-extension->
 use crate::common::sourcemap::SourcePos;
-use crate::parser::block::{BlockRule, BlockState};
+use crate::parser::block::{BlockRule, BlockState, InterruptTarget};
 use crate::parser::extset::RenderExt;
 use crate::parser::inline::InlineRoot;
 use crate::plugins::cmark::block::heading::HeadingScanner;
 use crate::plugins::cmark::block::list::ListScanner;
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Table {
     pub alignments: Vec<ColumnAlignment>,
 }
 
 impl NodeValue for Table {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let old_context = fmt.ext().remove::<TableRenderContext>();
         fmt.ext().insert(TableRenderContext { head: false, alignments: self.alignments.clone(), index: 0 });
@@ -40,10 +42,12 @@ pub struct TableRenderContext {
 
 impl RenderExt for TableRenderContext {}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TableHead;
 
 impl NodeValue for TableHead {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let ctx = fmt.ext().get_or_insert_default::<TableRenderContext>();
         ctx.head = true;
@@ -61,10 +65,12 @@ impl NodeValue for TableHead {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TableBody;
 
 impl NodeValue for TableBody {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
         fmt.open("tbody", &node.attrs);
@@ -76,10 +82,12 @@ impl NodeValue for TableBody {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TableRow;
 
 impl NodeValue for TableRow {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let ctx = fmt.ext().get_or_insert_default::<TableRenderContext>();
         ctx.index = 0;
@@ -94,10 +102,12 @@ impl NodeValue for TableRow {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TableCell;
 
 impl NodeValue for TableCell {
+    crate::impl_clone_value!();
+
     fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
         let ctx = fmt.ext().get_or_insert_default::<TableRenderContext>();
         let tag = if ctx.head { "th" } else { "td" };
@@ -121,7 +131,7 @@ impl NodeValue for TableCell {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<TableScanner>()
+    md.block.add_rule(TableScanner)
         .before::<ListScanner>()
         .before::<HeadingScanner>();
 }
@@ -135,18 +145,15 @@ struct RowContent {
     srcmap: Vec<(usize, usize)>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy)]
 pub enum ColumnAlignment {
+    #[default]
     None,
     Left,
     Right,
     Center,
 }
 
-impl Default for ColumnAlignment {
-    fn default() -> Self { Self::None }
-}
-
 impl TableScanner {
     fn scan_row(line: &str) -> Vec<RowContent> {
         let mut result = Vec::new();
@@ -292,13 +299,15 @@ impl TableScanner {
 }
 
 impl BlockRule for TableScanner {
-    fn check(state: &mut BlockState) -> Option<()> {
-        if state.node.is::<TableBody>() { return None; }
-
+    fn check(&self, state: &mut BlockState) -> Option<()> {
         Self::scan_header(state).map(|_| ())
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn can_interrupt(&self, target: InterruptTarget) -> bool {
+        target != InterruptTarget::Table
+    }
+
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         let ( header_row, alignments ) = Self::scan_header(state)?;
         let table_cell_count = header_row.len();
         let mut table_node = Node::new(Table { alignments });
@@ -352,7 +361,7 @@ impl BlockRule for TableScanner {
             if state.is_empty(state.line) { break; }
 
             // fail if terminating block found
-            if state.test_rules_at_line() { break; }
+            if state.test_rules_at_line(InterruptTarget::Table) { break; }
 
             let mut row_node = Node::new(TableRow);
             row_node.srcmap = state.get_map(state.line, state.line);
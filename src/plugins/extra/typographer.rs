@@ -74,13 +74,13 @@ fn replace_abbreviation(input: &str) -> &'static str {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.add_rule::<TypographerRule>();
+    md.add_rule(TypographerRule);
 }
 
 pub struct TypographerRule;
 
 impl CoreRule for TypographerRule {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(&self, root: &mut Node, _: &MarkdownIt) {
         root.walk_mut(|node, _| {
             let Some(text_node) = node.cast_mut::<Text>() else { return; };
 
@@ -9,12 +9,14 @@ use super::utils::regexps::*;
 use crate::parser::block::{BlockRule, BlockState};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HtmlBlock {
     pub content: String,
 }
 
 impl NodeValue for HtmlBlock {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.cr();
         fmt.text_raw(&self.content);
@@ -23,7 +25,7 @@ impl NodeValue for HtmlBlock {
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.block.add_rule::<HtmlBlockScanner>();
+    md.block.add_rule(HtmlBlockScanner);
 }
 
 struct HTMLSequence {
@@ -114,13 +116,13 @@ impl HtmlBlockScanner {
 }
 
 impl BlockRule for HtmlBlockScanner {
-    fn check(state: &mut BlockState) -> Option<()> {
+    fn check(&self, state: &mut BlockState) -> Option<()> {
         let sequence = Self::get_sequence(state)?;
         if !sequence.can_terminate_paragraph { return None; }
         Some(())
     }
 
-    fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
         let sequence = Self::get_sequence(state)?;
 
         let line_text = state.get_line(state.line);
@@ -2,30 +2,32 @@
 //!
 //! <https://spec.commonmark.org/0.30/#raw-html>
 use super::utils::regexps::*;
-use crate::parser::inline::{InlineRule, InlineState};
+use crate::parser::inline::{InlineRule, InlineState, Trigger};
 use crate::{MarkdownIt, Node, NodeValue, Renderer};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct HtmlInline {
     pub content: String,
 }
 
 impl NodeValue for HtmlInline {
+    crate::impl_clone_value!();
+
     fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
         fmt.text_raw(&self.content);
     }
 }
 
 pub fn add(md: &mut MarkdownIt) {
-    md.inline.add_rule::<HtmlInlineScanner>();
+    md.inline.add_rule(HtmlInlineScanner);
 }
 
 #[doc(hidden)]
 pub struct HtmlInlineScanner;
 impl InlineRule for HtmlInlineScanner {
-    const MARKER: char = '<';
+    fn trigger(&self) -> Trigger { Trigger::Char('<') }
 
-    fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+    fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
         // Check start
         let mut chars = state.src[state.pos..state.pos_max].chars();
         if chars.next().unwrap() != '<' { return None; }
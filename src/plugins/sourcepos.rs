@@ -7,29 +7,112 @@
 //! let html = md.parse("# hello").render();
 //! assert_eq!(html.trim(), r#"<h1 data-sourcepos="1:1-1:7">hello</h1>"#);
 //! ```
+//!
+//! Attribute name, its format and whether inline nodes are annotated can be
+//! changed with [add_with]. For example, [add_data_line] adds `data-line`
+//! with 0-based start line to block nodes only, same as markdown-it.js
+//! scroll-sync plugins expect:
+//! ```rust
+//! let md = &mut markdown_it::MarkdownIt::new();
+//! markdown_it::plugins::cmark::add(md);
+//! markdown_it::plugins::sourcepos::add_data_line(md);
+//!
+//! let html = md.parse("# hello\n\n*world*").render();
+//! assert_eq!(html, "<h1 data-line=\"0\">hello</h1>\n<p data-line=\"2\"><em>world</em></p>\n");
+//! ```
 use crate::common::sourcemap::SourceWithLineStarts;
 use crate::parser::block::builtin::BlockParserRule;
 use crate::parser::core::{CoreRule, Root};
 use crate::parser::inline::builtin::InlineParserRule;
+use crate::parser::inline::InlineRoot;
 use crate::{MarkdownIt, Node};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Format of source position attribute value.
+pub enum SourcePosFormat {
+    /// `startline:startcol-endline:endcol`, 1-based, inclusive (cmark-compatible).
+    LineColumn,
+    /// 0-based start line, e.g. `3`.
+    StartLine,
+    /// Byte offsets in the source, `start-end` (end is exclusive).
+    ByteOffsets,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Options for [add_with].
+pub struct SourcePosOptions {
+    /// Attribute name, `data-sourcepos` by default.
+    pub attr: &'static str,
+    /// Format of attribute value, [SourcePosFormat::LineColumn] by default.
+    pub format: SourcePosFormat,
+    /// Only annotate block nodes (paragraphs, headings, list items, etc.), `false` by default.
+    pub blocks_only: bool,
+}
+
+impl Default for SourcePosOptions {
+    fn default() -> Self {
+        Self {
+            attr: "data-sourcepos",
+            format: SourcePosFormat::LineColumn,
+            blocks_only: false,
+        }
+    }
+}
+
+/// Add `data-sourcepos="l:c-l:c"` to all nodes.
 pub fn add(md: &mut MarkdownIt) {
-    md.add_rule::<SyntaxPosRule>()
-        .after::<BlockParserRule>()
-        .after::<InlineParserRule>();
+    add_with(md, SourcePosOptions::default());
+}
+
+/// Add `data-line` with 0-based start line to block nodes.
+pub fn add_data_line(md: &mut MarkdownIt) {
+    add_with(md, SourcePosOptions {
+        attr: "data-line",
+        format: SourcePosFormat::StartLine,
+        blocks_only: true,
+    });
+}
+
+/// Add source positions with custom options.
+pub fn add_with(md: &mut MarkdownIt, options: SourcePosOptions) {
+    let rule = md.add_rule(SyntaxPosRule(options))
+        .after::<BlockParserRule>();
+
+    // inline nodes don't exist yet before inline parser is run
+    if options.blocks_only {
+        rule.before::<InlineParserRule>();
+    } else {
+        rule.after::<InlineParserRule>();
+    }
 }
 
 #[doc(hidden)]
-pub struct SyntaxPosRule;
+pub struct SyntaxPosRule(SourcePosOptions);
 impl CoreRule for SyntaxPosRule {
-    fn run(root: &mut Node, _: &MarkdownIt) {
+    fn run(&self, root: &mut Node, _: &MarkdownIt) {
+        let options = &self.0;
         let source = root.cast::<Root>().unwrap().content.as_str();
         let mapping = SourceWithLineStarts::new(source);
 
         root.walk_mut(|node, _| {
+            if node.is::<InlineRoot>() { return; }
+
             if let Some(map) = node.srcmap {
-                let ((startline, startcol), (endline, endcol)) = map.get_positions(&mapping);
-                node.attrs.push(("data-sourcepos", format!("{}:{}-{}:{}", startline, startcol, endline, endcol)));
+                let value = match options.format {
+                    SourcePosFormat::LineColumn => {
+                        let ((startline, startcol), (endline, endcol)) = map.get_positions(&mapping);
+                        format!("{}:{}-{}:{}", startline, startcol, endline, endcol)
+                    }
+                    SourcePosFormat::StartLine => {
+                        let (start, _) = map.get_byte_offsets();
+                        (mapping.get_line_column(start).0 - 1).to_string()
+                    }
+                    SourcePosFormat::ByteOffsets => {
+                        let (start, end) = map.get_byte_offsets();
+                        format!("{}-{}", start, end)
+                    }
+                };
+                node.attrs.push((options.attr, value));
             }
         });
     }
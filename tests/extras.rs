@@ -127,12 +127,18 @@ r#"<blockquote>
             "<p>There is a newline in this image  <img src=\"https://github.com/executablebooks/\" alt=\"here\nit is\"></p>");
     }
 
+    #[test]
+    fn regression_test_emphasis_in_blockquote() {
+        run("> **note**\n> read *this\n> now*",
+            "<blockquote>\n<p><strong>note</strong>\nread <em>this\nnow</em></p>\n</blockquote>");
+    }
+
     #[test]
     fn test_node_ext_propagation() {
         use markdown_it::parser::block::{BlockRule, BlockState};
         use markdown_it::parser::core::CoreRule;
         use markdown_it::parser::extset::NodeExt;
-        use markdown_it::parser::inline::{InlineRule, InlineState};
+        use markdown_it::parser::inline::{InlineRule, InlineState, Trigger};
         use markdown_it::{MarkdownIt, Node};
 
         #[derive(Debug, Default)]
@@ -141,9 +147,9 @@ r#"<blockquote>
 
         struct MyInlineRule;
         impl InlineRule for MyInlineRule {
-            const MARKER: char = '@';
+            fn trigger(&self) -> Trigger { Trigger::Char('@') }
 
-            fn run(state: &mut InlineState) -> Option<(Node, usize)> {
+            fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
                 let err = state.node.ext.get_or_insert_default::<NodeErrors>();
                 err.0.push("inline");
                 None
@@ -152,7 +158,7 @@ r#"<blockquote>
 
         struct MyBlockRule;
         impl BlockRule for MyBlockRule {
-            fn run(state: &mut BlockState) -> Option<(Node, usize)> {
+            fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
                 let err = state.node.ext.get_or_insert_default::<NodeErrors>();
                 err.0.push("block");
                 None
@@ -161,7 +167,7 @@ r#"<blockquote>
 
         struct MyCoreRule;
         impl CoreRule for MyCoreRule {
-            fn run(root: &mut Node, _md: &MarkdownIt) {
+            fn run(&self, root: &mut Node, _md: &MarkdownIt) {
                 let err = root.ext.get_or_insert_default::<NodeErrors>();
                 err.0.push("core");
             }
@@ -170,9 +176,9 @@ r#"<blockquote>
         let md = &mut markdown_it::MarkdownIt::new();
         markdown_it::plugins::cmark::add(md);
 
-        md.inline.add_rule::<MyInlineRule>();
-        md.block.add_rule::<MyBlockRule>();
-        md.add_rule::<MyCoreRule>().after_all();
+        md.inline.add_rule(MyInlineRule);
+        md.block.add_rule(MyBlockRule);
+        md.add_rule(MyCoreRule).after_all();
 
         let text1 = r#"*hello @world*"#;
         let ast = md.parse(text1);
@@ -191,6 +197,1853 @@ r#"<blockquote>
     }
 }
 
+mod configured_rules {
+    use markdown_it::generics::inline::emph_pair;
+    use markdown_it::parser::core::CoreRule;
+    use markdown_it::parser::inline::Text;
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Tag(&'static str);
+    impl NodeValue for Tag {
+        fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+            fmt.open(self.0, &node.attrs);
+            fmt.contents(&node.children);
+            fmt.close(self.0);
+        }
+    }
+
+    #[test]
+    fn emph_pair_with_closures() {
+        let md = &mut MarkdownIt::new();
+        for (marker, tag) in [('^', "sup"), ('=', "mark")] {
+            emph_pair::add_with(md, marker, 1, true, move || Node::new(Tag(tag)));
+        }
+        emph_pair::add_with(md, '=', 2, true, || Node::new(Tag("ins")));
+        let html = md.parse("^a^ =b= ==c==").render();
+        assert_eq!(html.trim(), "<sup>a</sup> <mark>b</mark> <ins>c</ins>");
+
+        md.inline.remove_rule_id(emph_pair::rule_id('^'));
+        let html = md.parse("^a^ =b=").render();
+        assert_eq!(html.trim(), "^a^ <mark>b</mark>");
+    }
+
+    #[test]
+    fn custom_smartquotes() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::extra::smartquotes::add_with(md, '‹', '›', '«', '»');
+        let html = md.parse("\"foo\" 'bar'").render();
+        assert_eq!(html.trim(), "<p>«foo» ‹bar›</p>");
+    }
+
+    #[test]
+    fn rule_instances_with_ids() {
+        struct Append(&'static str);
+        impl CoreRule for Append {
+            fn run(&self, root: &mut Node, _: &MarkdownIt) {
+                root.children.push(Node::new(Text { content: self.0.into() }));
+            }
+        }
+
+        let md = &mut MarkdownIt::new();
+        md.add_rule_with_id("world", Append("world"));
+        md.add_rule_with_id("hello", Append("hello ")).before_id("world");
+        assert!(md.has_rule::<Append>());
+        assert_eq!(md.parse("").render(), "hello world");
+
+        md.get_rule_mut::<Append>("world").unwrap().0 = "there";
+        assert_eq!(md.parse("").render(), "hello there");
+
+        md.remove_rule_id("hello");
+        assert!(md.has_rule_id("world"));
+        assert!(!md.has_rule_id("hello"));
+        assert_eq!(md.parse("").render(), "there");
+    }
+}
+
+mod inline_triggers {
+    use markdown_it::parser::inline::{InlineRule, InlineState, Trigger};
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Tag(String);
+    impl NodeValue for Tag {
+        fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+            fmt.open("b", &[]);
+            fmt.text(&self.0);
+            fmt.close("b");
+        }
+    }
+
+    // matches `@name` and `#name`
+    struct MentionScanner;
+    impl InlineRule for MentionScanner {
+        fn trigger(&self) -> Trigger { Trigger::Chars(vec!['@', '#']) }
+
+        fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
+            let input = &state.src[state.pos..state.pos_max];
+            if !input.starts_with(['@', '#']) { return None; }
+            let len = 1 + input[1..].find(|c: char| !c.is_alphanumeric()).unwrap_or(input.len() - 1);
+            if len == 1 { return None; }
+            Some((Node::new(Tag(input[..len].to_owned())), len))
+        }
+    }
+
+    // matches words starting with "www."
+    struct WwwScanner;
+    impl InlineRule for WwwScanner {
+        fn trigger(&self) -> Trigger { Trigger::WordStart(char::is_alphanumeric) }
+
+        fn run(&self, state: &mut InlineState) -> Option<(Node, usize)> {
+            let input = &state.src[state.pos..state.pos_max];
+            if !input.starts_with("www.") { return None; }
+            let len = input.find(char::is_whitespace).unwrap_or(input.len());
+            Some((Node::new(Tag(input[..len].to_owned())), len))
+        }
+    }
+
+    #[test]
+    fn multiple_chars() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        md.inline.add_rule(MentionScanner);
+        let html = md.parse("hi @foo, see #bar").render();
+        assert_eq!(html.trim(), "<p>hi <b>@foo</b>, see <b>#bar</b></p>");
+    }
+
+    #[test]
+    fn word_start() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        md.inline.add_rule(WwwScanner);
+        let html = md.parse("go to www.example.com or awww.example.com").render();
+        assert_eq!(html.trim(), "<p>go to <b>www.example.com</b> or awww.example.com</p>");
+        let html = md.parse("www.a and\nwww.b").render();
+        assert_eq!(html.trim(), "<p><b>www.a</b> and\n<b>www.b</b></p>");
+    }
+}
+
+mod block_interrupts {
+    use markdown_it::parser::block::{BlockRule, BlockState, InterruptTarget};
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Break;
+    impl NodeValue for Break {
+        fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+            fmt.cr();
+            fmt.self_close("br", &[]);
+            fmt.cr();
+        }
+    }
+
+    // matches `%%%`, only allowed to interrupt blockquotes
+    struct BreakScanner;
+    impl BlockRule for BreakScanner {
+        fn run(&self, state: &mut BlockState) -> Option<(Node, usize)> {
+            if state.get_line(state.line) != "%%%" { return None; }
+            Some((Node::new(Break), 1))
+        }
+
+        fn can_interrupt(&self, target: InterruptTarget) -> bool {
+            target == InterruptTarget::Blockquote
+        }
+    }
+
+    #[test]
+    fn interrupt_only_declared_targets() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        md.block.add_rule(BreakScanner);
+
+        let html = md.parse("foo\n%%%").render();
+        assert_eq!(html, "<p>foo\n%%%</p>\n");
+
+        let html = md.parse("> foo\n%%%").render();
+        assert_eq!(html, "<blockquote>\n<p>foo</p>\n</blockquote>\n<br>\n");
+    }
+}
+
+mod generic_blocks {
+    use markdown_it::generics::block::{fenced_container, fenced_raw, line_prefix};
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Container(&'static str);
+    impl NodeValue for Container {
+        fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+            fmt.cr();
+            fmt.open(self.0, &[]);
+            fmt.cr();
+            fmt.contents(&node.children);
+            fmt.cr();
+            fmt.close(self.0);
+            fmt.cr();
+        }
+    }
+
+    #[derive(Debug)]
+    struct Raw(String, String);
+    impl NodeValue for Raw {
+        fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+            fmt.cr();
+            fmt.open("pre", &[("data-info", self.0.clone())]);
+            fmt.text(&self.1);
+            fmt.close("pre");
+            fmt.cr();
+        }
+    }
+
+    fn md() -> MarkdownIt {
+        let mut md = MarkdownIt::new();
+        markdown_it::plugins::cmark::add(&mut md);
+        fenced_container::add_with(&mut md, ':', 3, |info| {
+            (info == "section").then(|| Node::new(Container("section")))
+        });
+        fenced_raw::add_with(&mut md, '%', 3, |info, content| {
+            Some(Node::new(Raw(info.to_owned(), content)))
+        });
+        line_prefix::add_with(&mut md, '|', || Node::new(Container("aside")));
+        md
+    }
+
+    #[test]
+    fn fenced_container_nesting() {
+        let html = md().parse("::::: section\n- *a*\n\n::: section\nb\n:::\n:::::\nc").render();
+        assert_eq!(html, "<section>\n<ul>\n<li><em>a</em></li>\n</ul>\n<section>\n<p>b</p>\n</section>\n</section>\n<p>c</p>\n");
+    }
+
+    #[test]
+    fn fenced_container_declined() {
+        let html = md().parse("::: other\nfoo").render();
+        assert_eq!(html, "<p>::: other\nfoo</p>\n");
+    }
+
+    #[test]
+    fn fenced_container_interrupts_paragraph() {
+        let html = md().parse("foo\n::: section\nbar").render();
+        assert_eq!(html, "<p>foo</p>\n<section>\n<p>bar</p>\n</section>\n");
+    }
+
+    #[test]
+    fn fenced_callback_called_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut md = MarkdownIt::new();
+        markdown_it::plugins::cmark::add(&mut md);
+        let counter = calls.clone();
+        fenced_raw::add_with(&mut md, '%', 3, move |_, content| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Some(Node::new(Raw(String::new(), content)))
+        });
+
+        // checking whether the fence interrupts a paragraph doesn't call it
+        let html = md.parse("foo\n%%%\nbar\n%%%").render();
+        assert_eq!(html, "<p>foo</p>\n<pre data-info=\"\">bar\n</pre>\n");
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn fenced_raw_content() {
+        let html = md().parse("%%% graph\n*a* -> b\n%%%%\nc").render();
+        assert_eq!(html, "<pre data-info=\"graph\">*a* -&gt; b\n</pre>\n<p>c</p>\n");
+    }
+
+    #[test]
+    fn fenced_raw_unclosed() {
+        let html = md().parse("%%%\nfoo").render();
+        assert_eq!(html, "<pre data-info=\"\">foo\n</pre>\n");
+    }
+
+    #[test]
+    fn blockquote_rule_id() {
+        use markdown_it::plugins::cmark::block::blockquote::BlockquoteScanner;
+
+        let mut md = md();
+        assert!(md.block.has_rule::<BlockquoteScanner>());
+        assert!(md.block.has_rule_id(line_prefix::rule_id('>')));
+
+        // `>` prefix added before blockquotes takes precedence
+        md.block.add_rule_with_id("aside", line_prefix::LinePrefixScanner::new('>', || Node::new(Container("aside"))))
+            .before::<BlockquoteScanner>();
+        assert_eq!(md.parse("> foo").render(), "<aside>\n<p>foo</p>\n</aside>\n");
+    }
+
+    #[test]
+    fn line_prefix_with_lazy_continuation() {
+        let html = md().parse("| foo\nbar\n| > baz").render();
+        assert_eq!(html, "<aside>\n<p>foo\nbar</p>\n<blockquote>\n<p>baz</p>\n</blockquote>\n</aside>\n");
+    }
+}
+
+mod generic_delimited {
+    use markdown_it::generics::inline::delimited::{self, Content};
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Tag(&'static str, String);
+    impl NodeValue for Tag {
+        fn render(&self, node: &Node, fmt: &mut dyn Renderer) {
+            fmt.open(self.0, &[("data-src", self.1.clone())]);
+            fmt.contents(&node.children);
+            fmt.close(self.0);
+        }
+    }
+
+    fn md(content: Content, nested: bool) -> MarkdownIt {
+        let mut md = MarkdownIt::new();
+        markdown_it::plugins::cmark::add(&mut md);
+        delimited::add_with(&mut md, "[[", "]]", content, nested, |src| {
+            Some(Node::new(Tag("wiki", src.to_owned())))
+        });
+        delimited::add_with(&mut md, "{%", "%}", Content::Raw, false, |src| {
+            src.starts_with(' ').then(|| Node::new(Tag("code", src.to_owned())))
+        });
+        md
+    }
+
+    #[test]
+    fn raw_content() {
+        let html = md(Content::Raw, false).parse("a [[*b* c]] d").render();
+        assert_eq!(html, "<p>a <wiki data-src=\"*b* c\"></wiki> d</p>\n");
+    }
+
+    #[test]
+    fn parsed_content() {
+        let html = md(Content::Parsed, false).parse("a [[*b* `]]` c]] d").render();
+        assert_eq!(html, "<p>a <wiki data-src=\"*b* `]]` c\"><em>b</em> <code>]]</code> c</wiki> d</p>\n");
+    }
+
+    #[test]
+    fn raw_nested() {
+        let html = md(Content::Raw, true).parse("[[a [[b]] c]]").render();
+        assert_eq!(html, "<p><wiki data-src=\"a [[b]] c\"></wiki></p>\n");
+
+        let html = md(Content::Raw, false).parse("[[a [[b]] c]]").render();
+        assert_eq!(html, "<p><wiki data-src=\"a [[b\"></wiki> c]]</p>\n");
+    }
+
+    #[test]
+    fn parsed_nested() {
+        let html = md(Content::Parsed, true).parse("[[a [[b]] c]]").render();
+        assert_eq!(html, "<p><wiki data-src=\"a [[b]] c\">a <wiki data-src=\"b\">b</wiki> c</wiki></p>\n");
+    }
+
+    #[test]
+    fn parsed_without_nesting() {
+        // `[b]` is scanned as a link label both in the whole line and inside of
+        // the content (which ends before `]]`), so cached label ends must depend
+        // on the end of the range being parsed
+        let html = md(Content::Parsed, false).parse("[[a [[b]] c]]").render();
+        assert_eq!(html, "<p><wiki data-src=\"a [[b\">a [[b</wiki> c]]</p>\n");
+    }
+
+    #[test]
+    fn unclosed_or_declined() {
+        let html = md(Content::Parsed, true).parse("[[a [[b]] {%c%} {% d %}").render();
+        assert_eq!(html, "<p>[[a <wiki data-src=\"b\">b</wiki> {%c%} <code data-src=\" d \"></code></p>\n");
+    }
+}
+
+mod link_context {
+    use markdown_it::generics::inline::full_link::{self, LinkKind};
+    use markdown_it::parser::extset::RootExt;
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[derive(Debug)]
+    struct Ref {
+        kind: LinkKind,
+        text: String,
+        reference: Option<String>,
+        index: usize,
+    }
+
+    impl NodeValue for Ref {
+        fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+            fmt.text(&format!("{}:{:?}:{}:{:?}", self.index, self.kind, self.text, self.reference));
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct RefCounter(usize);
+    impl RootExt for RefCounter {}
+
+    #[test]
+    fn link_kinds_and_labels() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        full_link::add_prefix(md, '~', false, |ctx| {
+            let counter = ctx.root_ext.get_or_insert_default::<RefCounter>();
+            counter.0 += 1;
+            Node::new(Ref {
+                kind: ctx.kind,
+                text: ctx.text,
+                reference: ctx.reference,
+                index: counter.0,
+            })
+        });
+
+        let html = md.parse("~[*a*](b) ~[c][Foo] ~[foo][] ~[FOO]\n\n[foo]: /url").render();
+        assert_eq!(html, concat!(
+            "<p>1:Inline:*a*:None ",
+            "2:Full:c:Some(&quot;Foo&quot;) ",
+            "3:Collapsed:foo:Some(&quot;foo&quot;) ",
+            "4:Shortcut:FOO:Some(&quot;FOO&quot;)</p>\n",
+        ));
+    }
+}
+
+mod link_forms {
+    use markdown_it::generics::inline::full_link::LinkKind;
+    use markdown_it::plugins::cmark::block::reference::Definition;
+    use markdown_it::plugins::cmark::inline::image::Image;
+    use markdown_it::plugins::cmark::inline::link::Link;
+    use markdown_it::MarkdownIt;
+
+    #[test]
+    fn link_kind_and_reference() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        let ast = md.parse("[a](/a) [b][Foo] [foo][] ![FOO]\n\n[foo]: /foo");
+
+        let mut links = vec![];
+        ast.walk(|node, _| {
+            if let Some(link) = node.cast::<Link>() {
+                links.push((link.kind, link.reference.clone()));
+            } else if let Some(image) = node.cast::<Image>() {
+                links.push((image.kind, image.reference.clone()));
+            }
+        });
+
+        assert_eq!(links, [
+            (LinkKind::Inline, None),
+            (LinkKind::Full, Some("Foo".into())),
+            (LinkKind::Collapsed, Some("foo".into())),
+            (LinkKind::Shortcut, Some("FOO".into())),
+        ]);
+    }
+
+    #[test]
+    fn definition_backlinks() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        let ast = md.parse("[x][foo] [y]\n\n[foo]: /foo\n[FOO]: /dup\n[bar]: /bar");
+
+        let mut used_by = vec![];
+        ast.walk(|node, _| {
+            if let Some(definition) = node.cast::<Definition>() {
+                let offsets: Vec<_> = definition.used_by.iter().map(|pos| pos.get_byte_offsets()).collect();
+                used_by.push((definition.label.clone(), offsets));
+            }
+        });
+
+        assert_eq!(used_by, [
+            ("foo".into(), vec![(0, 8)]),
+            ("FOO".into(), vec![]),
+            ("bar".into(), vec![]),
+        ]);
+    }
+}
+
+mod broken_references {
+    use markdown_it::generics::inline::full_link::{self, LinkKind};
+    use markdown_it::parser::core::Root;
+    use markdown_it::parser::extset::RootExt;
+    use markdown_it::MarkdownIt;
+
+    #[derive(Debug, Default)]
+    struct Missing(Vec<(LinkKind, String, (usize, usize))>);
+    impl RootExt for Missing {}
+
+    #[test]
+    fn callback_called_once_per_link() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        full_link::set_broken_reference_callback(md, |broken| {
+            let missing = broken.root_ext.get_or_insert_default::<Missing>();
+            let range = broken.srcmap.unwrap().get_byte_offsets();
+            missing.0.push((broken.kind, broken.reference.to_owned(), range));
+            (broken.reference == "known").then(|| ("/known".into(), Some("title".into())))
+        });
+
+        let ast = md.parse("[a [b][c]](/x) ![d][] [known]\n\n[e]: /e\n\n[e]");
+        assert_eq!(ast.render(), concat!(
+            "<p><a href=\"/x\">a [b][c]</a> ![d][] ",
+            "<a href=\"/known\" title=\"title\">known</a></p>\n",
+            "<p><a href=\"/e\">e</a></p>\n",
+        ));
+
+        let root = ast.cast::<Root>().unwrap();
+        let mut missing = root.ext.get::<Missing>().unwrap().0.clone();
+        missing.sort_by_key(|(_, _, range)| *range);
+        assert_eq!(missing, [
+            (LinkKind::Full, "c".into(), (3, 9)),
+            // inner brackets are checked as well, since they could be a link too
+            (LinkKind::Shortcut, "c".into(), (6, 9)),
+            (LinkKind::Collapsed, "d".into(), (15, 21)),
+            (LinkKind::Collapsed, "d".into(), (16, 21)),
+            (LinkKind::Shortcut, "known".into(), (22, 29)),
+        ]);
+    }
+
+    #[test]
+    fn callback_result_is_validated() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        full_link::set_broken_reference_callback(md, |broken| {
+            match broken.reference {
+                "js" => Some(("javascript:alert(1)".into(), None)),
+                _ => Some(("/a b".into(), None)),
+            }
+        });
+
+        let html = md.parse("[js] [foo]").render();
+        assert_eq!(html, "<p>[js] <a href=\"/a%20b\">foo</a></p>\n");
+    }
+}
+
+mod diagnostics {
+    use markdown_it::parser::core::Root;
+    use markdown_it::parser::diagnostics::{Diagnostics, Severity};
+    use markdown_it::MarkdownIt;
+
+    fn run(md: &MarkdownIt, input: &str) -> Vec<(String, (usize, usize))> {
+        let ast = md.parse(input);
+        let root = ast.cast::<Root>().unwrap();
+        let Some(diagnostics) = root.ext.get::<Diagnostics>() else { return vec![]; };
+        diagnostics.iter().map(|d| {
+            assert_eq!(d.severity, Severity::Warning);
+            (d.code.to_string(), d.srcmap.unwrap().get_byte_offsets())
+        }).collect()
+    }
+
+    fn md() -> MarkdownIt {
+        let mut md = MarkdownIt::new();
+        markdown_it::plugins::cmark::add(&mut md);
+        md
+    }
+
+    #[test]
+    fn no_diagnostics() {
+        assert_eq!(run(&md(), "# hello\n\n2 * 3 *is* 6 [x]\n\n[x]: /x\n\n```\ncode\n```"), []);
+    }
+
+    #[test]
+    fn unclosed_fence() {
+        assert_eq!(run(&md(), "foo\n- ```\n  bar"), [("unclosed-fence".into(), (6, 9))]);
+    }
+
+    #[test]
+    fn references() {
+        assert_eq!(run(&md(), "[a]\n\n[a]: /a\n[A]: /b\n[c]: /c"), [
+            ("duplicate-definition".into(), (13, 20)),
+            ("unused-definition".into(), (21, 28)),
+        ]);
+    }
+
+    #[test]
+    fn unmatched_emphasis() {
+        assert_eq!(run(&md(), "***foo** bar_"), [
+            ("unmatched-emphasis".into(), (0, 1)),
+            ("unmatched-emphasis".into(), (12, 13)),
+        ]);
+    }
+
+    #[test]
+    fn unmatched_emphasis_too_short() {
+        let mut md = md();
+        markdown_it::plugins::extra::strikethrough::add(&mut md);
+        assert_eq!(run(&md, "cd ~/foo and ~bar"), []);
+        assert_eq!(run(&md, "~~foo"), [("unmatched-emphasis".into(), (0, 2))]);
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let mut md = md();
+        md.max_nesting = 3;
+        assert_eq!(run(&md, "> > > > foo"), [("nesting-limit".into(), (6, 11))]);
+        assert_eq!(run(&md, "- > foo\n- > - bar"), [("nesting-limit".into(), (14, 17))]);
+        assert_eq!(run(&md, "[[a](b)](c)"), []);
+        markdown_it::plugins::cmark::inline::image::add(&mut md);
+        assert_eq!(run(&md, "![![![a](b)](c)](d)"), [("nesting-limit".into(), (6, 7))]);
+    }
+}
+
+mod lint {
+    use markdown_it::lint::{rules, LintContext, LintRule, Linter};
+    use markdown_it::parser::diagnostics::Severity;
+    use markdown_it::MarkdownIt;
+
+    fn run(linter: &Linter, source: &str) -> Vec<(String, usize)> {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::html::add(md);
+        let diagnostics = linter.lint(&md.parse(source), source);
+        diagnostics.iter().map(|d| (d.code.to_string(), d.srcmap.unwrap().get_byte_offsets().0)).collect()
+    }
+
+    #[test]
+    fn builtin_rules() {
+        let linter = Linter::new();
+        assert_eq!(run(&linter, "# a\n\ntext  \nmore\n\n- [x](https://x.org) <https://y.org>\n- `https://z.org`\n"), []);
+
+        assert_eq!(run(&linter, "# a\n### b\n# c\n\n* x\n\n- y\t\n\n![](z.png) http://example.org, \n"), [
+            ("heading-increment".into(), 4),
+            ("single-h1".into(), 10),
+            ("ul-style".into(), 20),
+            ("no-trailing-spaces".into(), 23),
+            ("no-alt-text".into(), 26),
+            ("no-bare-urls".into(), 37),
+            ("no-trailing-spaces".into(), 56),
+        ]);
+    }
+
+    #[test]
+    fn line_length() {
+        let mut linter = Linter::empty();
+        linter.add(rules::LineLength { max: 10, code_blocks: false, headings: true });
+        let source = "short line\nthis line is long\nhttps://long.example.org\n\n    code block is long\n";
+        assert_eq!(run(&linter, source), [("line-length".into(), 21)]);
+    }
+
+    #[test]
+    fn configuration() {
+        let mut linter = Linter::new();
+        linter.get_mut::<rules::UlStyle>().unwrap().marker = Some('-');
+        linter.remove("MD001");
+        assert!(!linter.has_rule("heading-increment"));
+        assert!(linter.set_severity("ul-style", Severity::Error));
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        let source = "# a\n### b\n\n* x\n";
+        let diagnostics = linter.lint(&md.parse(source), source);
+        let found: Vec<_> = diagnostics.iter().map(|d| (d.code.as_ref(), d.severity)).collect();
+        assert_eq!(found, [("ul-style", Severity::Error)]);
+    }
+
+    #[test]
+    fn disable_comments() {
+        let linter = Linter::new();
+        let source = concat!(
+            "# a\n",
+            "# b <!-- lint-disable-line single-h1 -->\n",
+            "<!-- markdownlint-disable-next-line MD025 -->\n",
+            "# c\n",
+            "<!-- lint-disable -->\n",
+            "# d\n",
+            "<!-- lint-enable -->\n",
+            "<!-- lint-disable no-alt-text no-trailing-spaces -->\n",
+            "# e\n",
+            "![](x.png) \n",
+        );
+        assert_eq!(run(&linter, source), [("single-h1".into(), source.find("# e").unwrap())]);
+    }
+
+    #[test]
+    fn custom_rule() {
+        struct NoTodo;
+        impl LintRule for NoTodo {
+            fn code(&self) -> &'static str { "no-todo" }
+            fn check(&self, ctx: &mut LintContext) {
+                let found: Vec<_> = ctx.lines().filter_map(|(offset, line)| {
+                    line.find("TODO").map(|pos| offset + pos)
+                }).collect();
+                for pos in found {
+                    ctx.report("unfinished document", Some(markdown_it::common::sourcemap::SourcePos::new(pos, pos + 4)));
+                }
+            }
+        }
+
+        let mut linter = Linter::empty();
+        linter.add(NoTodo);
+        assert_eq!(run(&linter, "foo\nbar TODO\n"), [("no-todo".into(), 8)]);
+    }
+}
+
+mod fence_info {
+    use markdown_it::plugins::cmark::block::fence::{self, FenceInfo};
+    use markdown_it::MarkdownIt;
+
+    #[test]
+    fn parse() {
+        let info = FenceInfo::parse(r#"rust title="main.rs" {1,3-5} showLineNumbers"#);
+        assert_eq!(info.lang.as_deref(), Some("rust"));
+        assert_eq!(info.attrs, [("title".to_owned(), "main.rs".to_owned())]);
+        assert_eq!(info.flags, ["showLineNumbers"]);
+        assert_eq!(info.lines, [1..=1, 3..=5]);
+        assert_eq!(info.title(), Some("main.rs"));
+        assert!(info.show_line_numbers());
+        assert!(info.is_highlighted(4));
+        assert!(!info.is_highlighted(2));
+    }
+
+    #[test]
+    fn parse_attribute_blocks() {
+        let info = FenceInfo::parse("{.class #id .other key='a b' flag}");
+        assert_eq!(info.lang, None);
+        assert_eq!(info.attrs, [
+            ("class".to_owned(), "class other".to_owned()),
+            ("id".to_owned(), "id".to_owned()),
+            ("key".to_owned(), "a b".to_owned()),
+        ]);
+        assert_eq!(info.flags, ["flag"]);
+
+        let info = FenceInfo::parse("js{4} filename=app.js");
+        assert_eq!(info.lang.as_deref(), Some("js"));
+        assert_eq!(info.lines, [4..=4]);
+        assert_eq!(info.title(), Some("app.js"));
+
+        let info = FenceInfo::parse(r#"ruby startline=3 $%@#$"#);
+        assert_eq!(info.lang.as_deref(), Some("ruby"));
+        assert_eq!(info.attr("startline"), Some("3"));
+        assert_eq!(info.flags, ["$%@#$"]);
+
+        assert_eq!(FenceInfo::parse("  "), FenceInfo::default());
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let html = md.parse("```{r} title=x {2}\na\nb\n```").render();
+        assert_eq!(html, "<pre><code class=\"language-{r}\">a\nb\n</code></pre>\n");
+
+        let html = md.parse("```a=b {.c}\nx\n```").render();
+        assert_eq!(html, "<pre><code class=\"language-a=b\">x\n</code></pre>\n");
+    }
+
+    #[test]
+    fn render_highlighted_lines() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        fence::enable_extended_info(md);
+
+        let html = md.parse("```text {2}\na\n<b>\n\n```").render();
+        assert_eq!(html, "\
+<pre><code class=\"language-text\"><span class=\"line\">a</span>
+<span class=\"line highlighted\">&lt;b&gt;</span>
+<span class=\"line\"></span>
+</code></pre>
+");
+    }
+
+    #[test]
+    fn render_without_lang() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        fence::enable_extended_info(md);
+
+        let html = md.parse("``` {#x} title=\"<a>\"\nfoo\n```").render();
+        assert_eq!(html, "\
+<figure class=\"code-block\">
+<figcaption>&lt;a&gt;</figcaption>
+<pre><code id=\"x\">foo
+</code></pre>
+</figure>
+");
+    }
+}
+
+mod fence_handlers {
+    use markdown_it::plugins::cmark::block::fence::{self, CodeFence};
+    use markdown_it::plugins::extra::fence_handlers::{self, MarkdownPreview};
+    use markdown_it::MarkdownIt;
+
+    #[test]
+    fn math_and_markdown() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        fence_handlers::add(md);
+
+        let html = md.parse("```math\nx < y\n```").render();
+        assert_eq!(html, "<div class=\"math display\">\\[x &lt; y\\]</div>\n");
+
+        let ast = md.parse("~~~markdown\n*hi*\n~~~");
+        assert!(ast.children[0].is::<MarkdownPreview>());
+        assert_eq!(ast.render(), "\
+<div class=\"markdown-preview\">
+<div class=\"preview\">
+<p><em>hi</em></p>
+</div>
+<pre><code class=\"language-markdown\">*hi*
+</code></pre>
+</div>
+");
+    }
+
+    #[test]
+    fn csv_table() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        fence_handlers::add_csv(md);
+
+        let html = md.parse("```csv\na,b\n1,*2*\n```").render();
+        assert_eq!(html, "\
+<table>
+<thead>
+<tr>
+<th>a</th>
+<th>b</th>
+</tr>
+</thead>
+<tbody>
+<tr>
+<td>1</td>
+<td>*2*</td>
+</tr>
+</tbody>
+</table>
+");
+        // empty csv is kept as a code block
+        let html = md.parse("```csv\n```").render();
+        assert_eq!(html, "<pre><code class=\"language-csv\"></code></pre>\n");
+    }
+
+    #[test]
+    fn markdown_preview_in_document() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::sourcepos::add(md);
+        fence_handlers::add(md);
+
+        let html = md.parse("foo\n\n  ```markdown\n  [x] *y*\n  ```").render();
+        assert_eq!(html, "\
+<p data-sourcepos=\"1:1-1:3\">foo</p>
+<div data-sourcepos=\"3:3-5:5\" class=\"markdown-preview\">
+<div class=\"preview\">
+<p data-sourcepos=\"4:3-4:9\">[x] <em data-sourcepos=\"4:7-4:9\">y</em></p>
+</div>
+<pre><code class=\"language-markdown\">[x] *y*
+</code></pre>
+</div>
+");
+    }
+
+    #[test]
+    fn markdown_preview_references_are_isolated() {
+        use markdown_it::parser::core::Root;
+        use markdown_it::parser::diagnostics::Diagnostics;
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        fence_handlers::add(md);
+
+        let ast = md.parse("[x] [z]\n\n```markdown\n[x] [z]\n\n[x]: /x\n[x]: /y\n```\n\n[z]: /z");
+        assert_eq!(ast.render(), "\
+<p>[x] <a href=\"/z\">z</a></p>
+<div class=\"markdown-preview\">
+<div class=\"preview\">
+<p><a href=\"/x\">x</a> [z]</p>
+</div>
+<pre><code class=\"language-markdown\">[x] [z]
+
+[x]: /x
+[x]: /y
+</code></pre>
+</div>
+");
+
+        let root = ast.cast::<Root>().unwrap();
+        assert!(root.ext.get::<Diagnostics>().is_none());
+    }
+
+    #[test]
+    fn handler_can_decline() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        fence::add_handler(md, "graphviz", |fence, _| {
+            fence.parse_info().has_flag("render").then(|| {
+                markdown_it::Node::new(fence_handlers::Diagram { lang: "graphviz".into(), content: fence.content.clone() })
+            })
+        });
+
+        let ast = md.parse("```graphviz render\na\n```\n```graphviz\nb\n```");
+        assert!(ast.children[0].is::<fence_handlers::Diagram>());
+        assert!(ast.children[1].is::<CodeFence>());
+
+        fence::remove_handler(md, "graphviz");
+        let ast = md.parse("```graphviz render\na\n```");
+        assert!(ast.children[0].is::<CodeFence>());
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn not_highlighted() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::extra::syntect::add(md);
+        fence_handlers::add_diagram(md, "mermaid");
+
+        let html = md.parse("```mermaid\ngraph TD;\n```\n```rust\nfn x() {}\n```").render();
+        assert!(html.starts_with("<pre class=\"mermaid\">graph TD;\n</pre>\n<pre style="));
+    }
+}
+
+mod inline_code_lang {
+    use markdown_it::parser::highlighter;
+    use markdown_it::plugins::cmark::inline::backticks::{self, CodeInline};
+    use markdown_it::MarkdownIt;
+
+    #[derive(Debug)]
+    struct Upper;
+    impl highlighter::Highlighter for Upper {
+        fn highlight(&self, _: Option<&str>, code: &str) -> Option<String> {
+            Some(code.to_uppercase())
+        }
+
+        fn pre_attrs(&self) -> Vec<(&'static str, String)> {
+            vec![("class", "hl".into())]
+        }
+
+        fn inline_attrs(&self) -> Vec<(&'static str, String)> {
+            vec![("data-hl", "upper".into())]
+        }
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        let html = md.parse("`x`{:rust}").render();
+        assert_eq!(html.trim(), "<p><code>x</code>{:rust}</p>");
+    }
+
+    #[test]
+    fn lang_suffix() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        backticks::enable_lang_suffix(md);
+
+        let ast = md.parse("`a`{.py} `b` {:rust} `c`{:bad lang} `d`{:}");
+        let langs: Vec<_> = ast.children[0].children.iter()
+            .filter_map(|node| node.cast::<CodeInline>())
+            .map(|code| code.lang.as_deref())
+            .collect();
+        assert_eq!(langs, [Some("py"), None, None, None]);
+        assert_eq!(ast.children[0].children[0].srcmap.unwrap().get_byte_offsets(), (0, 8));
+        assert_eq!(ast.render().trim(), "\
+<p><code class=\"language-py\">a</code> <code>b</code> {:rust} <code>c</code>{:bad lang} <code>d</code>{:}</p>");
+    }
+
+    #[test]
+    fn highlighted() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        backticks::enable_lang_suffix(md);
+        highlighter::set_highlighter(md, Upper);
+
+        let html = md.parse("`let x`{:rust} and `y`").render();
+        assert_eq!(html.trim(), r#"<p><code class="language-rust" data-hl="upper">LET X</code> and <code>y</code></p>"#);
+    }
+
+    #[cfg(feature = "syntect")]
+    #[test]
+    fn syntect() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::extra::syntect::add(md);
+        backticks::enable_lang_suffix(md);
+
+        let html = md.parse("`fn main()`{:rust}").render();
+        assert!(html.starts_with(r#"<p><code class="language-rust" style="background-color:#ffffff;"><span style="#));
+        assert!(html.contains(">main</span>"));
+    }
+}
+
+mod node_lookup {
+    use markdown_it::common::sourcemap::SourceWithLineStarts;
+    use markdown_it::parser::core::Root;
+    use markdown_it::parser::inline::Text;
+    use markdown_it::plugins::cmark::block::heading::ATXHeading;
+    use markdown_it::plugins::cmark::block::list::{BulletList, ListItem};
+    use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+    use markdown_it::plugins::cmark::inline::emphasis::Strong;
+    use markdown_it::{MarkdownIt, Node};
+
+    fn names(nodes: &[&Node]) -> Vec<&'static str> {
+        nodes.iter().map(|node| {
+            let name = node.name();
+            &name[name.rfind("::").map(|x| x + 2).unwrap_or_default()..]
+        }).collect()
+    }
+
+    #[test]
+    fn node_at() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let source = "# head\n\n- one\n- **two**\n";
+        let ast = md.parse(source);
+
+        let path = ast.node_at(source.find("wo").unwrap());
+        assert!(path[0].is::<Root>());
+        assert!(path[1].is::<BulletList>());
+        assert!(path[2].is::<ListItem>());
+        assert!(path[3].is::<Strong>());
+        assert!(path[4].is::<Text>());
+        assert_eq!(path.len(), 5);
+
+        let path = ast.node_at(2);
+        assert_eq!(names(&path), ["Root", "ATXHeading", "Text"]);
+        assert!(path[1].is::<ATXHeading>());
+
+        // line break between blocks belongs to the root only
+        assert_eq!(names(&ast.node_at(7)), ["Root"]);
+        assert!(ast.node_at(source.len()).is_empty());
+
+        let map = SourceWithLineStarts::new(source);
+        let offset = map.get_byte_offset(3, 3).unwrap();
+        assert_eq!(names(&ast.node_at(offset)), ["Root", "BulletList", "ListItem", "Text"]);
+    }
+
+    #[test]
+    fn nodes_in_range() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let source = "one\n\ntwo *three*\n\nfour";
+        let ast = md.parse(source);
+
+        let start = source.find("two").unwrap();
+        let nodes = ast.nodes_in_range(start, start + 6);
+        assert_eq!(names(&nodes), ["Root", "Paragraph", "Text", "Em", "Text"]);
+        assert!(nodes[1].is::<Paragraph>());
+
+        let nodes = ast.nodes_in_range(0, source.len());
+        assert_eq!(nodes.len(), 9);
+
+        let nodes = ast.nodes_in_range(1, 1);
+        assert_eq!(names(&nodes), ["Root", "Paragraph", "Text"]);
+    }
+}
+
+mod sourcepos_options {
+    use markdown_it::plugins::sourcepos::{self, SourcePosFormat, SourcePosOptions};
+    use markdown_it::MarkdownIt;
+
+    fn render(options: SourcePosOptions, source: &str) -> String {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        sourcepos::add_with(md, options);
+        md.parse(source).render()
+    }
+
+    #[test]
+    fn blocks_only() {
+        let html = render(SourcePosOptions { blocks_only: true, ..Default::default() }, "- *a*\n- `b`");
+        assert_eq!(html, "<ul data-sourcepos=\"1:1-2:5\">\n\
+            <li data-sourcepos=\"1:1-1:5\"><em>a</em></li>\n\
+            <li data-sourcepos=\"2:1-2:5\"><code>b</code></li>\n</ul>\n");
+    }
+
+    #[test]
+    fn byte_offsets_with_custom_attr() {
+        let options = SourcePosOptions { attr: "data-range", format: SourcePosFormat::ByteOffsets, blocks_only: false };
+        let html = render(options, "# ☃\n\nfoo *bar*");
+        assert_eq!(html, "<h1 data-range=\"0-5\">☃</h1>\n\
+            <p data-range=\"7-16\">foo <em data-range=\"11-16\">bar</em></p>\n");
+    }
+
+    #[test]
+    fn start_line() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        sourcepos::add_data_line(md);
+
+        let html = md.parse("> foo\n>\n> - bar\n\n```\ncode\n```").render();
+        assert_eq!(html, "<blockquote data-line=\"0\">\n<p data-line=\"0\">foo</p>\n\
+            <ul data-line=\"2\">\n<li data-line=\"2\">bar</li>\n</ul>\n</blockquote>\n\
+            <pre><code data-line=\"4\">code\n</code></pre>\n");
+    }
+}
+
+mod query {
+    use markdown_it::parser::inline::Text;
+    use markdown_it::plugins::cmark::block::heading::ATXHeading;
+    use markdown_it::plugins::cmark::block::list::{BulletList, ListItem};
+    use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+    use markdown_it::plugins::cmark::inline::link::Link;
+    use markdown_it::MarkdownIt;
+
+    fn parse(source: &str) -> markdown_it::Node {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        md.parse(source)
+    }
+
+    #[test]
+    fn descendants_in_preorder() {
+        let ast = parse("# a\n\n- b\n- *c*");
+        let names: Vec<_> = ast.descendants()
+            .map(|node| node.name().rsplit("::").next().unwrap())
+            .collect();
+        assert_eq!(names, ["ATXHeading", "Text", "BulletList", "ListItem", "Text", "ListItem", "Em", "Text"]);
+    }
+
+    #[test]
+    fn typed_iterators() {
+        let ast = parse("# a\n\n- b\n- c");
+        let (_, heading) = ast.find_first::<ATXHeading>().unwrap();
+        assert_eq!(heading.level, 1);
+        assert!(ast.find_first::<Paragraph>().is_none());
+
+        let (list, _) = ast.children_of::<BulletList>().next().unwrap();
+        assert_eq!(list.children_of::<ListItem>().count(), 2);
+        assert_eq!(ast.children_of::<ListItem>().count(), 0);
+
+        let texts: Vec<_> = ast.descendants_of::<Text>().map(|(_, text)| text.content.as_str()).collect();
+        assert_eq!(texts, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn ancestors() {
+        let ast = parse("> - [link](/url)");
+        let (link, _) = ast.find_first::<Link>().unwrap();
+        let names: Vec<_> = ast.ancestors_of(link).unwrap().iter()
+            .map(|node| node.name().rsplit("::").next().unwrap())
+            .collect();
+        assert_eq!(names, ["Root", "Blockquote", "BulletList", "ListItem"]);
+
+        let (node, ancestors) = ast.descendants_with_ancestors().last().unwrap();
+        assert!(node.is::<Text>());
+        assert!(std::ptr::eq(*ancestors.last().unwrap(), link));
+
+        let other = parse("[link](/url)");
+        assert!(ast.ancestors_of(&other).is_none());
+    }
+
+    #[test]
+    fn selectors() {
+        let ast = parse("- [a](/a)\n\n  > [b](/b)\n\n[c](/c)\n\n1. [d](/d)");
+        let urls = |selector: &str| -> Vec<String> {
+            ast.select(selector).unwrap().iter()
+                .map(|node| node.cast::<Link>().unwrap().url.clone())
+                .collect()
+        };
+
+        assert_eq!(urls("Link"), ["/a", "/b", "/c", "/d"]);
+        assert_eq!(urls("BulletList Link"), ["/a", "/b"]);
+        assert_eq!(urls("BulletList > ListItem > Paragraph > Link"), ["/a"]);
+        // paragraphs in tight lists are removed
+        assert_eq!(urls("ListItem > Link"), ["/d"]);
+        assert_eq!(urls("ListItem > * > Link"), ["/a"]);
+        assert_eq!(urls("Root > Paragraph > Link"), ["/c"]);
+        assert_eq!(urls("cmark::block::list::OrderedList Link"), ["/d"]);
+        assert!(ast.select("Blockquote >").is_err());
+    }
+
+    #[test]
+    fn selectors_in_deep_trees() {
+        // each ancestor is checked once per selector part, otherwise
+        // this would try every combination of 20 out of 60 blockquotes
+        let ast = parse(&format!("{}foo", "> ".repeat(60)));
+        let selector = format!("Paragraph {}Paragraph", "Blockquote ".repeat(20));
+        assert!(ast.select(&selector).unwrap().is_empty());
+
+        let selector = format!("Root {}Paragraph", "Blockquote ".repeat(20));
+        assert_eq!(ast.select(&selector).unwrap().len(), 1);
+    }
+}
+
+mod cursor {
+    use markdown_it::common::sourcemap::SourcePos;
+    use markdown_it::parser::inline::Text;
+    use markdown_it::plugins::cmark::block::blockquote::Blockquote;
+    use markdown_it::plugins::cmark::block::paragraph::Paragraph;
+    use markdown_it::plugins::cmark::inline::emphasis::{Em, Strong};
+    use markdown_it::{MarkdownIt, Node};
+
+    fn parse(source: &str) -> Node {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        md.parse(source)
+    }
+
+    #[test]
+    fn navigation() {
+        let mut ast = parse("a *b*\n\nc");
+        let mut cursor = ast.cursor();
+        assert!(!cursor.goto_parent());
+        assert!(!cursor.goto_next_sibling());
+
+        assert!(cursor.goto_first_child());
+        assert!(cursor.node().is::<Paragraph>());
+        assert!(cursor.goto_last_child());
+        assert!(cursor.node().is::<Em>());
+        assert_eq!((cursor.depth(), cursor.index()), (2, Some(1)));
+        assert!(cursor.parent().unwrap().is::<Paragraph>());
+
+        assert!(cursor.goto_prev_sibling());
+        assert!(!cursor.goto_prev_sibling());
+        assert_eq!(cursor.node().collect_text(), "a ");
+
+        let mut visited = Vec::new();
+        while cursor.goto_next() {
+            visited.push(cursor.node().collect_text());
+        }
+        assert_eq!(visited, ["b", "b", "c", "c"]);
+        assert_eq!(cursor.node().collect_text(), "c");
+
+        while cursor.goto_prev() {}
+        assert_eq!(cursor.depth(), 0);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut ast = parse("a *b* **c** d");
+        let mut cursor = ast.cursor();
+        while cursor.goto_next() {
+            if cursor.node().is::<Em>() {
+                assert!(cursor.insert_before(Node::new(Text { content: "[".into() })));
+                assert!(cursor.insert_after(Node::new(Text { content: "]".into() })));
+                assert!(cursor.node().is::<Em>());
+            } else if cursor.node().is::<Strong>() {
+                let removed = cursor.remove().unwrap();
+                assert_eq!(removed.collect_text(), "c");
+                assert_eq!(cursor.node().collect_text(), " ");
+            }
+        }
+        assert_eq!(ast.render(), "<p>a [<em>b</em>]  d</p>\n");
+
+        assert!(ast.cursor().remove().is_none());
+        assert!(!ast.cursor().insert_after(Node::default()));
+    }
+
+    #[test]
+    fn wrap_and_unwrap() {
+        let mut ast = parse("a\n\nb\n\nc");
+        let mut cursor = ast.cursor();
+        cursor.goto_first_child();
+        assert!(cursor.wrap(2, Node::new(Blockquote)));
+        assert_eq!(cursor.node().srcmap, Some(SourcePos::new(0, 4)));
+        assert_eq!(cursor.node().children.len(), 2);
+        assert!(!cursor.wrap(0, Node::new(Blockquote)));
+        drop(cursor);
+        assert_eq!(ast.render(), "<blockquote>\n<p>a</p>\n<p>b</p>\n</blockquote>\n<p>c</p>\n");
+
+        let mut cursor = ast.cursor();
+        let mut visited = Vec::new();
+        while cursor.goto_next() {
+            if cursor.node().is::<Blockquote>() {
+                let node = cursor.unwrap().unwrap();
+                assert!(node.is::<Blockquote>() && node.children.is_empty());
+            } else if cursor.node().is::<Paragraph>() {
+                visited.push(cursor.node().collect_text());
+            }
+        }
+        assert_eq!(visited, ["a", "b", "c"]);
+        assert_eq!(ast.render(), "<p>a</p>\n<p>b</p>\n<p>c</p>\n");
+    }
+
+    #[test]
+    fn split_text() {
+        let mut ast = parse("héllo &amp; world");
+        let mut cursor = ast.cursor();
+        cursor.goto_first_child();
+        cursor.goto_first_child();
+        assert!(cursor.node().is::<Text>());
+        assert!(!cursor.split_text(0));
+        assert!(!cursor.split_text(2)); // inside of "é"
+        assert!(cursor.split_text(3));
+        assert_eq!(cursor.node().cast::<Text>().unwrap().content, "hé");
+        assert_eq!(cursor.node().srcmap, Some(SourcePos::new(0, 3)));
+        assert!(cursor.goto_next_sibling());
+        assert_eq!(cursor.node().cast::<Text>().unwrap().content, "llo ");
+        assert_eq!(cursor.node().srcmap, Some(SourcePos::new(3, 7)));
+
+        // text doesn't match source (e.g. after replacing entities), so source mapping is copied
+        let mut text = Node::new(Text { content: "a&b".into() });
+        text.srcmap = Some(SourcePos::new(0, 7));
+        let mut paragraph = Node::new(Paragraph);
+        paragraph.children.push(text);
+        let mut cursor = paragraph.cursor();
+        assert!(!cursor.split_text(1));
+        cursor.goto_first_child();
+        assert!(cursor.split_text(1));
+        cursor.goto_next_sibling();
+        assert_eq!(cursor.node().cast::<Text>().unwrap().content, "&b");
+        assert_eq!(cursor.node().srcmap, Some(SourcePos::new(0, 7)));
+    }
+}
+
+mod clone_nodes {
+    use markdown_it::parser::core::Root;
+    use markdown_it::parser::diagnostics::Diagnostics;
+    use markdown_it::plugins::cmark::block::heading::ATXHeading;
+    use markdown_it::{MarkdownIt, Node, NodeValue, Renderer};
+
+    #[test]
+    fn clone_document() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::html::add(md);
+        markdown_it::plugins::extra::add(md);
+        markdown_it::plugins::extra::fence_handlers::add(md);
+
+        let source = "# Title\n\n> - *a* **b** ~~c~~ `d` <span>e</span>\n>\n> 1. [f](/f) ![g](/g) <https://h.com>\n\n\
+            | x | y |\n|---|:-:|\n| 1 | 2 |\n\n```csv\na,b\n```\n\n```rust\nfn main() {}\n```\n\n---\n\n[ref]: /ref\n\n```\n";
+        let ast = md.parse(source);
+        let copy = ast.try_clone().unwrap();
+        assert_eq!(copy.render(), ast.render());
+        let nodes = |ast: &Node| -> Vec<_> {
+            ast.descendants().map(|node| (node.name(), node.srcmap, format!("{:?}", node.node_value))).collect()
+        };
+        assert_eq!(nodes(&copy), nodes(&ast));
+
+        // warnings (e.g. unclosed fence) are kept
+        let diagnostics = |ast: &Node| ast.cast::<Root>().unwrap().ext.get::<Diagnostics>().unwrap().iter().count();
+        assert!(diagnostics(&ast) > 0);
+        assert_eq!(diagnostics(&copy), diagnostics(&ast));
+    }
+
+    #[test]
+    fn copy_subtree() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let mut ast = md.parse("# a\n\ntext\n\n## b");
+        let toc: Vec<Node> = ast.descendants_of::<ATXHeading>()
+            .map(|(node, _)| node.try_clone().unwrap())
+            .collect();
+        ast.children.splice(0..0, toc);
+        assert_eq!(ast.render(), "<h1>a</h1>\n<h2>b</h2>\n<h1>a</h1>\n<p>text</p>\n<h2>b</h2>\n");
+    }
+
+    #[test]
+    fn not_cloneable() {
+        #[derive(Debug)]
+        struct Custom;
+        impl NodeValue for Custom {
+            fn render(&self, _: &Node, fmt: &mut dyn Renderer) {
+                fmt.text("custom");
+            }
+        }
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let mut ast = md.parse("*foo*");
+        ast.children[0].children[0].children.push(Node::new(Custom));
+        let err = ast.try_clone().unwrap_err();
+        assert!(err.node_name.ends_with("::Custom"));
+        assert!(err.to_string().ends_with("::Custom doesn't implement clone_value"));
+        assert!(ast.children[0].children[0].children[0].try_clone().is_ok());
+    }
+}
+
+mod ast_diff {
+    use markdown_it::parser::diff::EditKind;
+    use markdown_it::MarkdownIt;
+
+    fn md() -> MarkdownIt {
+        let mut md = MarkdownIt::new();
+        markdown_it::plugins::cmark::add(&mut md);
+        markdown_it::plugins::extra::tables::add(&mut md);
+        md
+    }
+
+    fn same(old: &str, new: &str) -> bool {
+        let md = md();
+        md.parse(old).structural_eq(&md.parse(new))
+    }
+
+    type Change = (EditKind, String, String, Option<(usize, usize)>, Option<(usize, usize)>);
+
+    fn diff(old: &str, new: &str) -> Vec<Change> {
+        let md = md();
+        let (old, new) = (md.parse(old), md.parse(new));
+        let text = |nodes: &[markdown_it::Node]| nodes.iter().map(|node| node.collect_text()).collect::<String>();
+        old.diff(&new).into_iter().map(|edit| (
+            edit.kind,
+            text(edit.old),
+            text(edit.new),
+            edit.old_srcmap.map(|map| map.get_byte_offsets()),
+            edit.new_srcmap.map(|map| map.get_byte_offsets()),
+        )).collect()
+    }
+
+    #[test]
+    fn formatting_is_ignored() {
+        assert!(same("*a* __b__", "_a_ **b**"));
+        assert!(same("- a\n- b", "* a\n* b"));
+        assert!(same("1. a", "1) a"));
+        assert!(same("```rust\ncode\n```", "~~~~ rust\ncode\n~~~~"));
+        assert!(same("    code", "```\ncode\n```"));
+        assert!(same("[a][x]\n\n[x]: /url", "[a](/url)"));
+        assert!(same("foo\nbar   baz", "foo bar\nbaz"));
+        assert!(same("***", "- - -"));
+        assert!(same("|a|b|\n|-|-|\n|1|2|", "| a | b |\n|---|---|\n| 1 | 2 |"));
+    }
+
+    #[test]
+    fn content_changes_are_not_ignored() {
+        assert!(!same("*a*", "**a**"));
+        assert!(!same("# a", "## a"));
+        assert!(!same("```rust\ncode\n```", "```rust\ncode  \n```"));
+        assert!(!same("[a](/x)", "[a](/y)"));
+        assert!(!same("a", "a\n\nb"));
+        assert!(!same("|a|b|\n|-|-|", "|a|b|\n|-|:-|"));
+        assert!(same("", ""));
+    }
+
+    #[test]
+    fn insert_and_delete() {
+        assert_eq!(diff("a\n\nc", "a\n\nb\n\nc"), [
+            (EditKind::Insert, "".into(), "b".into(), Some((1, 1)), Some((3, 4))),
+        ]);
+        assert_eq!(diff("a\n\nb\n\nc", "a\n\nc"), [
+            (EditKind::Delete, "b".into(), "".into(), Some((3, 4)), Some((1, 1))),
+        ]);
+        assert_eq!(diff("a", "b\n\na"), [
+            (EditKind::Insert, "".into(), "b".into(), Some((0, 0)), Some((0, 1))),
+        ]);
+    }
+
+    #[test]
+    fn nested_updates() {
+        assert_eq!(diff("# title\n\n> quote *text*", "## title\n\n> quote *test*"), [
+            (EditKind::Update, "title".into(), "title".into(), Some((0, 7)), Some((0, 8))),
+            (EditKind::Update, "text".into(), "test".into(), Some((18, 22)), Some((19, 23))),
+        ]);
+
+        // paragraph turned into heading, text is the same
+        assert_eq!(diff("a\n\nb", "a\n\n# b"), [
+            (EditKind::Delete, "b".into(), "".into(), Some((3, 4)), Some((1, 1))),
+            (EditKind::Insert, "".into(), "b".into(), Some((4, 4)), Some((3, 6))),
+        ]);
+    }
+}
+
+mod render_diff {
+    fn render_diff(old: &str, new: &str) -> String {
+        let md = &mut markdown_it::MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::extra::tables::add(md);
+        markdown_it::plugins::extra::strikethrough::add(md);
+        markdown_it::parser::diff::render_diff(md, old, new)
+    }
+
+    #[test]
+    fn unchanged() {
+        let html = render_diff("Title\n=====\n\n*foo*\nbar", "# Title\n\n_foo_ bar");
+        assert_eq!(html, "<h1>Title</h1>\n<p><em>foo</em> bar</p>\n");
+    }
+
+    #[test]
+    fn words() {
+        let html = render_diff("hello brave\nworld & all", "hello new world &amp; all");
+        assert_eq!(html, "<p>hello <del>brave</del><ins>new</ins> world &amp; all</p>\n");
+
+        let html = render_diff("one two", "one two three");
+        assert_eq!(html, "<p>one two<ins> three</ins></p>\n");
+    }
+
+    #[test]
+    fn inline_nodes() {
+        let html = render_diff("foo *bar*", "foo **bar**");
+        assert_eq!(html, "<p>foo <del><em>bar</em></del><ins><strong>bar</strong></ins></p>\n");
+
+        let html = render_diff("foo `x` *bar*", "foo *bar* ~~baz~~ quux");
+        assert_eq!(html, "<p>foo <del><code>x</code> </del><em>bar</em><ins> <s>baz</s> quux</ins></p>\n");
+    }
+
+    #[test]
+    fn adjacent_inserts_are_merged() {
+        let md = &mut markdown_it::MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::html::add(md);
+
+        let html = markdown_it::parser::diff::render_diff(md, "hello *world* x", "hello **world** x <b>y</b>");
+        assert_eq!(html, "<p>hello <del><em>world</em></del><ins><strong>world</strong></ins> x<ins> <b>y</b></ins></p>\n");
+    }
+
+    #[test]
+    fn blocks() {
+        let html = render_diff("one\n\ntwo\n\nthree", "one\n\nthree\n\n# four");
+        assert_eq!(html, "<p>one</p>\n<p class=\"diff-del\">two</p>\n<p>three</p>\n<h1 class=\"diff-ins\">four</h1>\n");
+
+        let html = render_diff("```\nfoo\n```", "```\nbar\n```");
+        assert_eq!(html, "<pre class=\"diff-changed\"><code>bar\n</code></pre>\n");
+    }
+
+    #[test]
+    fn lists() {
+        let html = render_diff("- foo\n- bar\n- baz", "- foo\n- quux\n- bar");
+        assert_eq!(html, "<ul>\n<li>foo</li>\n<li class=\"diff-ins\">quux</li>\n<li>bar</li>\n<li class=\"diff-del\">baz</li>\n</ul>\n");
+
+        let html = render_diff("1. foo\n2. bar", "1. foo\n2. bar baz");
+        assert_eq!(html, "<ol>\n<li>foo</li>\n<li>bar<ins> baz</ins></li>\n</ol>\n");
+    }
+
+    #[test]
+    fn tables() {
+        let old = "| a | b |\n|---|:-:|\n| 1 | 2 |\n| 3 | 4 |";
+        let new = "| a | b |\n|---|:-:|\n| 1 | 5 |\n| 6 | 7 |";
+        assert_eq!(render_diff(old, new), "\
+<table>
+<thead>
+<tr>
+<th>a</th>
+<th style=\"text-align:center\">b</th>
+</tr>
+</thead>
+<tbody>
+<tr>
+<td>1</td>
+<td style=\"text-align:center\"><del>2</del><ins>5</ins></td>
+</tr>
+<tr>
+<td><del>3</del><ins>6</ins></td>
+<td style=\"text-align:center\"><del>4</del><ins>7</ins></td>
+</tr>
+</tbody>
+</table>
+");
+
+        let new = "| a | b |\n|---|:-:|\n| 3 | 4 |";
+        assert_eq!(render_diff(old, new), "\
+<table>
+<thead>
+<tr>
+<th>a</th>
+<th style=\"text-align:center\">b</th>
+</tr>
+</thead>
+<tbody>
+<tr class=\"diff-del\">
+<td>1</td>
+<td style=\"text-align:center\">2</td>
+</tr>
+<tr>
+<td>3</td>
+<td style=\"text-align:center\">4</td>
+</tr>
+</tbody>
+</table>
+");
+    }
+
+    #[test]
+    fn large_documents() {
+        // would need gigabytes of memory with a full lcs table
+        let old = (0..30000).map(|i| format!("p{i}")).collect::<Vec<_>>();
+        let mut new = old.clone();
+        new[15000] = "changed".into();
+        let html = render_diff(&old.join("\n\n"), &new.join("\n\n"));
+        assert!(html.contains("<p><del>p15000</del><ins>changed</ins></p>\n<p>p15001</p>"));
+
+        let html = render_diff(&old.join(" "), &new.join(" "));
+        assert!(html.contains(" <del>p15000</del><ins>changed</ins> p15001 "));
+    }
+}
+
+mod source_map {
+    use markdown_it::MarkdownIt;
+
+    /// Decode mappings into (generated line, column, source line, column), all 0-based.
+    fn decode(mappings: &str) -> Vec<(i64, i64, i64, i64)> {
+        const BASE64: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut result = Vec::new();
+        let (mut src_line, mut src_col) = (0, 0);
+        for (gen_line, line) in mappings.split(';').enumerate() {
+            let mut gen_col = 0;
+            for segment in line.split(',').filter(|s| !s.is_empty()) {
+                let mut values = Vec::new();
+                let (mut value, mut shift) = (0i64, 0);
+                for ch in segment.chars() {
+                    let digit = BASE64.find(ch).unwrap() as i64;
+                    value += (digit & 31) << shift;
+                    shift += 5;
+                    if digit & 32 == 0 {
+                        values.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+                        value = 0;
+                        shift = 0;
+                    }
+                }
+                gen_col += values[0];
+                src_line += values[2];
+                src_col += values[3];
+                result.push((gen_line as i64, gen_col, src_line, src_col));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn inline_nodes() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let source = "- ☃ **bold**\n- `code`";
+        let (html, map) = md.parse(source).render_with_source_map(source, "a.md");
+        assert_eq!(html, "<ul>\n<li>☃ <strong>bold</strong></li>\n<li><code>code</code></li>\n</ul>\n");
+
+        let segments = decode(&map.mappings);
+        // <strong> at line 1, column 6 (utf-16) maps to `**` at line 0, column 4
+        assert!(segments.contains(&(1, 6, 0, 4)));
+        // text inside of <strong>
+        assert!(segments.contains(&(1, 14, 0, 6)));
+        // <code> on the second item
+        assert!(segments.contains(&(2, 4, 1, 2)));
+
+        let json = map.to_json();
+        assert!(json.starts_with(r#"{"version":3,"sources":["a.md"],"sourcesContent":["- ☃ **bold**\n- `code`"],"names":[],"mappings":""#));
+    }
+
+    #[test]
+    fn closing_tags() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let source = "**bold**";
+        let (html, map) = md.parse(source).render_with_source_map(source, "a.md");
+        assert_eq!(html, "<p><strong>bold</strong></p>\n");
+
+        let segments = decode(&map.mappings);
+        // `</strong>` maps to the end of text inside of it
+        assert!(segments.contains(&(0, 15, 0, 6)));
+        // `</p>` maps to the end of `**bold**`
+        assert!(segments.contains(&(0, 24, 0, 8)));
+    }
+
+    #[test]
+    fn multiline_nodes() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        markdown_it::plugins::html::add(md);
+
+        let source = "    a\n    <b>\n\n```\nc\n  d\n```\n\n<div>\n  x\n</div>";
+        let (html, map) = md.parse(source).render_with_source_map(source, "a.md");
+        assert_eq!(html, "\
+<pre><code>a
+&lt;b&gt;
+</code></pre>
+<pre><code>c
+  d
+</code></pre>
+<div>
+  x
+</div>
+");
+
+        let segments = decode(&map.mappings);
+        // indentation is removed from code blocks
+        assert!(segments.contains(&(1, 0, 1, 4)));
+        // fence markers are skipped
+        assert!(segments.contains(&(4, 0, 5, 0)));
+        // html blocks are copied as is
+        assert!(segments.contains(&(7, 0, 9, 0)));
+        assert!(segments.contains(&(8, 0, 10, 0)));
+    }
+
+    #[test]
+    fn null_characters() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+
+        let source = "\0\0 *x*";
+        let (html, map) = md.parse(source).render_with_source_map(source, "a.md");
+        assert_eq!(html, "<p>\u{FFFD}\u{FFFD} <em>x</em></p>\n");
+        assert!(decode(&map.mappings).contains(&(0, 6, 0, 3)));
+    }
+}
+
+mod highlighter {
+    use markdown_it::parser::highlighter::{self, Highlighter};
+    use markdown_it::plugins::cmark::block::code::CodeBlock;
+    use markdown_it::plugins::cmark::block::fence::CodeFence;
+    use markdown_it::MarkdownIt;
+
+    #[derive(Debug)]
+    struct Brackets;
+    impl Highlighter for Brackets {
+        fn highlight(&self, lang: Option<&str>, code: &str) -> Option<String> {
+            if lang == Some("plain") { return None; }
+            Some(format!("[{}]{}", lang.unwrap_or("-"), code.replace('<', "&lt;")))
+        }
+
+        fn pre_attrs(&self) -> Vec<(&'static str, String)> {
+            vec![("class", "hl".into())]
+        }
+    }
+
+    #[test]
+    fn blocks_and_fences() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        highlighter::set_highlighter(md, Brackets);
+
+        let ast = md.parse("```rust\na<b\n```\n\n    indented\n\n```plain\na<b\n```");
+        assert!(ast.children[0].is::<CodeFence>());
+        assert!(ast.children[1].is::<CodeBlock>());
+        assert_eq!(ast.render(), "\
+<pre class=\"hl\"><code class=\"language-rust\">[rust]a&lt;b
+</code></pre>
+<pre class=\"hl\"><code>[-]indented
+</code></pre>
+<pre><code class=\"language-plain\">a&lt;b
+</code></pre>
+");
+    }
+
+    #[test]
+    fn remove_highlighter() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        highlighter::set_highlighter(md, Brackets);
+        assert!(highlighter::get_highlighter(md).unwrap().is::<Brackets>());
+        highlighter::remove_highlighter(md);
+        assert!(highlighter::get_highlighter(md).is_none());
+
+        let html = md.parse("```rust\nfoo\n```").render();
+        assert_eq!(html, "<pre><code class=\"language-rust\">foo\n</code></pre>\n");
+    }
+}
+
+#[cfg(feature = "syntect")]
+mod syntect {
+    use markdown_it::plugins::extra::syntect;
+    use markdown_it::MarkdownIt;
+
+    const FERRIS_SYNTAX: &str = r#"%YAML 1.2
+---
+name: Ferris
+file_extensions: [ferris]
+scope: source.ferris
+contexts:
+  main:
+    - match: '\bcrab\b'
+      scope: keyword.control.ferris
+"#;
+
+    #[test]
+    fn custom_theme_and_syntax_folder() {
+        let dir = std::env::temp_dir().join(format!("markdown-it-syntect-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Ferris.sublime-syntax"), FERRIS_SYNTAX).unwrap();
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        syntect::add(md);
+        syntect::add_syntaxes_from_folder(md, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(syntect::builtin_themes().any(|name| name == "InspiredGitHub"));
+        let mut theme = ::syntect::highlighting::ThemeSet::load_defaults().themes.remove("InspiredGitHub").unwrap();
+        theme.settings.background = Some(::syntect::highlighting::Color { r: 1, g: 2, b: 3, a: 0xFF });
+        syntect::set_custom_theme(md, theme);
+
+        let html = md.parse("```ferris\ncrab rave\n```").render();
+        assert!(html.starts_with(r#"<pre style="background-color:#010203;">"#));
+        assert!(html.contains(">crab</span>"));
+    }
+
+    #[test]
+    fn keeps_attrs_and_language_class() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        syntect::add(md);
+
+        let mut ast = md.parse("```rust title\nfn main() {}\n```");
+        ast.children[0].attrs.push(("id", "main".into()));
+        let html = ast.render();
+        assert!(html.starts_with(r#"<pre style="background-color:#ffffff;"><code id="main" class="language-rust"><span"#));
+        assert!(html.ends_with("</code></pre>\n"));
+    }
+
+    #[test]
+    fn keeps_code_nodes() {
+        use markdown_it::plugins::cmark::block::fence::CodeFence;
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        syntect::add(md);
+        syntect::set_theme(md, "base16-ocean.dark");
+
+        let ast = md.parse("```rust\nfn main() {}\n```");
+        let fence = ast.children[0].cast::<CodeFence>().unwrap();
+        assert_eq!(fence.content, "fn main() {}\n");
+        assert!(ast.render().starts_with(r#"<pre style="background-color:#2b303b;">"#));
+    }
+
+    #[test]
+    fn small_debug_output() {
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        syntect::add(md);
+
+        let ast = md.parse("```rust\nfn main() {}\n```");
+        assert!(format!("{:?}", ast).len() < 2000);
+    }
+
+    #[test]
+    fn class_output() {
+        use syntect::{ClassStyle, HighlightOutput};
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        syntect::add(md);
+        syntect::set_output(md, HighlightOutput::Classes(ClassStyle::SpacedPrefixed { prefix: "hl-" }));
+
+        let html = md.parse("```rust\nfn main() {}\n```\n\n    plain").render();
+        assert!(!html.contains("style="));
+        assert!(html.starts_with(r#"<pre class="hl-code"><code class="language-rust"><span class="hl-source hl-rust">"#));
+        assert!(html.contains(r#"<span class="hl-storage hl-type hl-function hl-rust">fn</span>"#));
+        assert!(html.contains(r#"<pre class="hl-code"><code><span class="hl-text hl-plain">plain"#));
+    }
+
+    #[test]
+    fn class_output_with_line_numbers() {
+        use syntect::{ClassStyle, HighlightOutput};
+
+        let md = &mut MarkdownIt::new();
+        markdown_it::plugins::cmark::add(md);
+        syntect::add(md);
+        syntect::set_output(md, HighlightOutput::Classes(ClassStyle::Spaced));
+        markdown_it::plugins::cmark::block::fence::enable_extended_info(md);
+
+        let html = md.parse("```rust linenos\nfn main() {\n}\n```").render();
+        let lines: Vec<_> = html.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2], "</code></pre>");
+        assert!(lines[0].starts_with(r#"<pre class="code"><code class="language-rust"><span class="line"><span class="line-number">1</span><span class="source rust">"#));
+        assert!(lines[1].starts_with(r#"<span class="line"><span class="line-number">2</span><span class="source rust">"#));
+        for line in &lines[..2] {
+            assert_eq!(line.matches("<span").count(), line.matches("</span>").count());
+        }
+    }
+
+    #[test]
+    fn css_generation() {
+        use syntect::ClassStyle;
+
+        let light = syntect::builtin_theme("InspiredGitHub").unwrap();
+        let dark = syntect::builtin_theme("base16-ocean.dark").unwrap();
+        assert!(syntect::builtin_theme("no such theme").is_none());
+
+        let css = syntect::css_for_theme(light, ClassStyle::SpacedPrefixed { prefix: "hl-" });
+        assert!(css.contains(".hl-code {\n color: #323232;\n background-color: #ffffff;\n}"));
+
+        let css = syntect::css_for_light_dark(light, dark, ClassStyle::Spaced);
+        let (light_css, dark_css) = css.split_once("@media (prefers-color-scheme: dark) {\n").unwrap();
+        assert!(light_css.contains(" background-color: #ffffff;\n"));
+        assert!(dark_css.contains("  .code {\n   color: #c0c5ce;\n   background-color: #2b303b;\n  }\n"));
+        assert!(dark_css.ends_with("}\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown syntect theme")]
+    fn unknown_theme() {
+        let md = &mut MarkdownIt::new();
+        syntect::set_theme(md, "no such theme");
+    }
+}
+
 mod examples {
     include!("../examples/ferris/main.rs");
 